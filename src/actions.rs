@@ -1,9 +1,18 @@
 //! Actions implementation.
 
 pub mod build;
+pub mod check;
 pub mod clean;
+pub mod discover;
 pub mod generate;
 pub mod init;
+pub mod remove;
+pub mod rename;
 pub mod schema;
+pub mod templates;
 pub mod test;
+pub mod upgrade;
 mod utils;
+pub mod verify;
+pub mod verify_bundle;
+pub mod workspace;
@@ -1,20 +1,37 @@
 //! Module for managing and building wasm files.
 
-use super::utils;
-use crate::{command, errors::Error, log, paths, project::Project};
+use super::{check::CheckAction, utils};
+use crate::{
+    build_options::BuildOptions,
+    command,
+    errors::Error,
+    log,
+    paths,
+    project::Project,
+    reproducible,
+};
 
 /// BuildAction configuration.
 pub struct BuildAction<'a> {
     contracts_names: Option<String>,
+    reproducible: bool,
+    options: BuildOptions,
     project: &'a Project,
 }
 
 /// BuildAction implementation.
 impl<'a> BuildAction<'a> {
     /// Crate a new BuildAction for a given backend.
-    pub fn new(project: &'a Project, contracts_names: Option<String>) -> Self {
+    pub fn new(
+        project: &'a Project,
+        contracts_names: Option<String>,
+        reproducible: bool,
+        options: BuildOptions,
+    ) -> Self {
         BuildAction {
             contracts_names,
+            reproducible,
+            options,
             project,
         }
     }
@@ -23,10 +40,17 @@ impl<'a> BuildAction<'a> {
 impl BuildAction<'_> {
     /// Main function that runs the whole workflow for a backend.
     pub fn build(&self) {
+        if self.reproducible {
+            reproducible::prepare_environment(&self.project.project_root());
+        }
         utils::check_target_requirements();
         utils::validate_contract_name_argument(self.project, self.contracts_names());
+        CheckAction::new(self.project).warn();
         self.build_wasm_files();
         self.optimize_wasm_files();
+        if self.reproducible {
+            self.record_build_info();
+        }
     }
 
     /// Build .wasm files.
@@ -39,27 +63,40 @@ impl BuildAction<'_> {
                 Error::FailedToParseArgument("contracts_names".to_string()).print_and_die()
             });
 
+        let mut extra_args = match self.reproducible {
+            true => reproducible::cargo_args(&self.project.project_root()),
+            false => vec![],
+        };
+        // Cargo rejects flags given twice.
+        extra_args
+            .retain(|arg| !(arg == "--locked" && self.options.passthrough_args.contains(arg)));
+
         for contract in contracts {
             let build_contract = format!("{}_build_contract", &contract.crate_name(self.project));
+            let mut args = self.options.cargo_args();
+            args.extend(contract.cargo_args(self.project));
+            args.extend_from_slice(&extra_args);
+            args.extend_from_slice(&self.options.passthrough_args);
             command::cargo_build_wasm_files(
                 self.project.project_root(),
                 &contract.struct_name(),
                 &contract.crate_name(self.project),
+                &args,
+            );
+            let source = paths::wasm_path_in_target(
+                &build_contract,
+                self.project.project_root(),
+                &self.options,
             );
-            let source = paths::wasm_path_in_target(&build_contract, self.project.project_root());
             let target =
-                paths::wasm_path_in_wasm_dir(&contract.struct_name(), &self.project.project_root());
+                paths::wasm_path_in_wasm_dir(&contract.wasm_name(), &self.project.project_root());
             log::info(format!("Saving {}", target.display()));
             command::cp(source.clone(), target);
             // if it's a workspace, copy the file also to the module wasm folder
             if self.project.is_workspace() {
-                let module_wasm_dir = self
-                    .project
-                    .project_root()
-                    .join(contract.module_name())
-                    .join("wasm");
+                let module_wasm_dir = contract.crate_root(self.project).join("wasm");
                 command::mkdir(module_wasm_dir.clone());
-                let mut module_wasm_path = module_wasm_dir.clone().join(&contract.struct_name());
+                let mut module_wasm_path = module_wasm_dir.clone().join(contract.wasm_name());
                 module_wasm_path.set_extension("wasm");
                 log::info(format!("Copying to {}", module_wasm_path.display()));
                 command::cp(source, module_wasm_path);
@@ -76,16 +113,25 @@ impl BuildAction<'_> {
             });
 
         for contract in contracts {
-            command::process_wasm(&contract.struct_name(), self.project.project_root());
+            command::process_wasm(&contract.wasm_name(), self.project.project_root());
             if self.project.is_workspace() {
-                command::process_wasm(
-                    &contract.struct_name(),
-                    self.project.project_root().join(contract.module_name()),
-                );
+                command::process_wasm(&contract.wasm_name(), contract.crate_root(self.project));
             }
         }
     }
 
+    /// Records hashes and tool versions of the built wasm files.
+    fn record_build_info(&self) {
+        let contracts =
+            utils::contracts(self.project, self.contracts_names()).unwrap_or_else(|_| {
+                Error::FailedToParseArgument("contracts_names".to_string()).print_and_die()
+            });
+
+        for contract in contracts {
+            reproducible::record_build_info(&contract, &self.project.project_root());
+        }
+    }
+
     fn contracts_names(&self) -> String {
         self.contracts_names.clone().unwrap_or_default()
     }
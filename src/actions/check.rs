@@ -0,0 +1,126 @@
+//! Module responsible for checking if Odra.toml matches the code.
+
+use std::{collections::HashMap, path::PathBuf};
+
+use crate::{errors::Error, log, odra_toml::Contract, project::Project, sources::CrateSources};
+
+/// CheckAction configuration.
+pub struct CheckAction<'a> {
+    project: &'a Project,
+}
+
+/// CheckAction implementation.
+impl<'a> CheckAction<'a> {
+    /// Creates a CheckAction for a given project.
+    pub fn new(project: &'a Project) -> Self {
+        CheckAction { project }
+    }
+}
+
+impl CheckAction<'_> {
+    /// Checks Odra.toml and exits with an error if there are any problems.
+    pub fn check(&self) {
+        let problems = self.problems();
+        if !problems.is_empty() {
+            problems.iter().for_each(log::error);
+            Error::OdraTomlCheckFailed(problems.len()).print_and_die();
+        }
+        log::info("Odra.toml is valid.");
+    }
+
+    /// Checks Odra.toml and reports the problems as warnings, without failing.
+    ///
+    /// Used before builds, as fqns pointing to macro-generated modules can not be resolved
+    /// from the sources, but still build.
+    pub fn warn(&self) {
+        let problems = self.problems();
+        if !problems.is_empty() {
+            problems.iter().for_each(log::warn);
+            log::warn("Odra.toml may not match the code, see `cargo odra check`.");
+        }
+    }
+
+    /// Returns descriptions of all problems found in Odra.toml.
+    fn problems(&self) -> Vec<String> {
+        let contracts = self.project.odra_toml().contracts;
+        let mut scanned = HashMap::new();
+        let mut problems: Vec<String> = contracts
+            .iter()
+            .filter_map(|contract| self.resolve(contract, &mut scanned).err())
+            .collect();
+        problems.extend(Self::duplicates(&contracts));
+        problems
+    }
+
+    /// Checks if the contract's fqn points to a `#[odra::module]` struct in the named crate,
+    /// directly or through re-exports. Each crate is scanned once.
+    fn resolve(
+        &self,
+        contract: &Contract,
+        scanned: &mut HashMap<PathBuf, Result<CrateSources, String>>,
+    ) -> Result<(), String> {
+        if contract.fqn.split_terminator("::").any(str::is_empty) {
+            return Err(format!("{}: malformed fqn.", contract.fqn));
+        }
+        if self.project.is_workspace()
+            && !self
+                .project
+                .members
+                .iter()
+                .any(|member| member.is_named(&contract.module_name()))
+        {
+            return Err(format!(
+                "{}: {} is not a member of the workspace.",
+                contract.fqn,
+                contract.module_name()
+            ));
+        }
+
+        let sources = scanned
+            .entry(contract.crate_root(self.project))
+            .or_insert_with_key(|crate_root| {
+                CrateSources::scan(crate_root).map_err(|err| err.to_string())
+            })
+            .as_ref()
+            .map_err(|err| format!("{}: {}", contract.fqn, err))?;
+        let module_path = contract.module_path(self.project);
+        match sources.contains(&module_path, &contract.struct_name()) {
+            true => Ok(()),
+            false => Err(format!(
+                "{}: there is no #[odra::module] struct {} in module {}.",
+                contract.fqn,
+                contract.struct_name(),
+                match module_path.is_empty() {
+                    true => "crate".to_string(),
+                    false => module_path.join("::"),
+                }
+            )),
+        }
+    }
+
+    /// Finds contracts with the same wasm name, as their wasm files collide in the wasm
+    /// directory.
+    fn duplicates(contracts: &[Contract]) -> Vec<String> {
+        let mut by_name: HashMap<String, Vec<&str>> = HashMap::new();
+        contracts.iter().for_each(|contract| {
+            by_name
+                .entry(contract.wasm_name())
+                .or_default()
+                .push(&contract.fqn)
+        });
+
+        let mut duplicates: Vec<String> = by_name
+            .into_iter()
+            .filter(|(_, fqns)| fqns.len() > 1)
+            .map(|(name, fqns)| {
+                format!(
+                    "{} is defined more than once ({}), its wasm files would overwrite each other.",
+                    name,
+                    fqns.join(", ")
+                )
+            })
+            .collect();
+        duplicates.sort();
+        duplicates
+    }
+}
@@ -1,14 +1,176 @@
 //! Module responsible for cleaning Odra projects.
 
-use crate::{command, project::Project};
+use std::path::PathBuf;
 
-/// Removes wasm folders, and runs `cargo clean`.
-pub fn clean_action(project: &Project) {
-    project.members.iter().for_each(|member| {
-        command::rm_dir(member.root.join("wasm"));
-    });
+use crate::{
+    cargo_toml::load_cargo_toml,
+    command,
+    errors::Error,
+    odra_toml::Contract,
+    paths,
+    project::{Member, Project},
+};
 
-    command::rm_dir(project.project_root().join("wasm"));
+/// CleanAction configuration.
+pub struct CleanAction<'a> {
+    project: &'a Project,
+    contracts_names: Vec<String>,
+    member: Option<String>,
+    wasm_only: bool,
+    schemas: bool,
+    all: bool,
+}
+
+/// CleanAction implementation.
+impl<'a> CleanAction<'a> {
+    /// Creates a CleanAction for a given project.
+    pub fn new(
+        project: &'a Project,
+        contracts_names: Vec<String>,
+        member: Option<String>,
+        wasm_only: bool,
+        schemas: bool,
+        all: bool,
+    ) -> Self {
+        CleanAction {
+            project,
+            contracts_names,
+            member,
+            wasm_only,
+            schemas,
+            all,
+        }
+    }
+}
+
+impl CleanAction<'_> {
+    /// Removes wasm files, and unless only some contracts are cleaned, cargo artifacts
+    /// of the Odra crates, or with `all`, of the whole project.
+    pub fn clean(&self) {
+        let members = self.members();
+        let contracts = self.contracts();
+
+        if !self.contracts_names.is_empty() {
+            contracts.iter().for_each(|contract| {
+                contract
+                    .wasm_roots(self.project)
+                    .iter()
+                    .flat_map(|root| paths::wasm_artifacts(&contract.wasm_name(), root))
+                    .filter(|path| path.exists())
+                    .for_each(command::rm_file);
+            });
+        } else if self.member.is_some() {
+            members
+                .iter()
+                .for_each(|member| command::rm_dir(paths::wasm_dir(&member.root)));
+            // The project's wasm directory is shared, remove only the member's files from it.
+            contracts
+                .iter()
+                .flat_map(|contract| {
+                    paths::wasm_artifacts(&contract.wasm_name(), &self.project.project_root())
+                })
+                .filter(|path| path.exists())
+                .for_each(command::rm_file);
+        } else {
+            members
+                .iter()
+                .for_each(|member| command::rm_dir(paths::wasm_dir(&member.root)));
+            command::rm_dir(paths::wasm_dir(&self.project.project_root()));
+        }
+
+        if self.schemas {
+            self.remove_schemas(&contracts);
+        }
+
+        if self.wasm_only || !self.contracts_names.is_empty() {
+            return;
+        }
+        match self.all {
+            true => command::cargo_clean(self.project.project_root(), &[]),
+            false => command::cargo_clean(self.project.project_root(), &self.packages(&members)),
+        }
+    }
+
+    /// Removes schema files of the contracts.
+    fn remove_schemas(&self, contracts: &[Contract]) {
+        contracts
+            .iter()
+            .flat_map(|contract| {
+                let mut roots = contract.wasm_roots(self.project);
+                roots.push(contract.crate_root(self.project));
+                roots.dedup();
+                roots
+                    .iter()
+                    .flat_map(|root| paths::schema_paths(&contract.struct_name(), root))
+                    .collect::<Vec<PathBuf>>()
+            })
+            .filter(|path| path.exists())
+            .for_each(command::rm_file);
+    }
+
+    /// Returns the workspace members to clean.
+    fn members(&self) -> Vec<&Member> {
+        match &self.member {
+            None => self.project.members.iter().collect(),
+            Some(name) => {
+                let member = self
+                    .project
+                    .members
+                    .iter()
+                    .find(|member| member.is_named(name))
+                    .unwrap_or_else(|| Error::ModuleNotFound(name.clone()).print_and_die());
+                vec![member]
+            }
+        }
+    }
+
+    /// Returns contracts to clean: the given ones, or all contracts of the member if it is set.
+    fn contracts(&self) -> Vec<Contract> {
+        let members = self.members();
+        let contracts: Vec<Contract> = self
+            .project
+            .odra_toml()
+            .contracts
+            .into_iter()
+            .filter(|contract| {
+                self.member.is_none()
+                    || members
+                        .iter()
+                        .any(|member| member.is_named(&contract.module_name()))
+            })
+            .collect();
+
+        if self.contracts_names.is_empty() {
+            return contracts;
+        }
+        self.contracts_names
+            .iter()
+            .map(|name| {
+                contracts
+                    .iter()
+                    .find(|contract| contract.is_named(name))
+                    .cloned()
+                    .unwrap_or_else(|| {
+                        let available = contracts.iter().map(Contract::wasm_name).collect();
+                        Error::ContractNotFound(name.clone(), available).print_and_die()
+                    })
+            })
+            .collect()
+    }
 
-    command::cargo_clean(project.project_root());
+    /// Returns names of the cargo packages of the Odra crates.
+    fn packages(&self, members: &[&Member]) -> Vec<String> {
+        match self.project.is_workspace() {
+            true => members
+                .iter()
+                .map(|member| {
+                    load_cargo_toml(&member.cargo_toml)
+                        .package
+                        .map(|package| package.name)
+                        .unwrap_or_else(|| member.name.clone())
+                })
+                .collect(),
+            false => vec![self.project.name.clone()],
+        }
+    }
 }
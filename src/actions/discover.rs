@@ -0,0 +1,144 @@
+//! Module responsible for discovering contracts in the project's sources.
+
+use std::path::PathBuf;
+
+use crate::{
+    cargo_toml::load_cargo_toml,
+    errors::Error,
+    log,
+    odra_toml::{Contract, OdraToml},
+    project::Project,
+    sources::CrateSources,
+};
+
+/// DiscoverAction configuration.
+pub struct DiscoverAction {
+    project_root: PathBuf,
+    write: bool,
+}
+
+/// DiscoverAction implementation.
+impl DiscoverAction {
+    /// Creates a DiscoverAction for a project containing the given directory.
+    pub fn new(current_dir: PathBuf, write: bool) -> Self {
+        let project_root = Project::find_project_root(current_dir)
+            .unwrap_or_else(|| Error::NotAnOdraProject.print_and_die());
+        DiscoverAction {
+            project_root,
+            write,
+        }
+    }
+}
+
+impl DiscoverAction {
+    /// Prints contracts found in the sources, and if requested, adds the missing ones
+    /// to Odra.toml.
+    pub fn discover(&self) {
+        log::info("Scanning sources...");
+        let crates = self.scan_crates();
+        let discovered: Vec<Contract> = crates
+            .iter()
+            .flat_map(|(crate_name, sources)| {
+                sources.deployable().map(move |module| {
+                    let mut segments: Vec<String> = crate_name.iter().cloned().collect();
+                    segments.extend(module.module_path.iter().cloned());
+                    segments.push(module.struct_name.clone());
+                    Contract::new(segments.join("::"))
+                })
+            })
+            .collect();
+
+        let odra_toml_path = self.project_root.join("Odra.toml");
+        let mut odra_toml = match odra_toml_path.exists() {
+            true => OdraToml::load(&odra_toml_path),
+            false => OdraToml {
+                contracts: vec![],
+                location: odra_toml_path,
+            },
+        };
+
+        let missing: Vec<Contract> = discovered
+            .iter()
+            .filter(|contract| !odra_toml.contracts.iter().any(|c| c.fqn == contract.fqn))
+            .cloned()
+            .collect();
+        let stale: Vec<Contract> = odra_toml
+            .contracts
+            .iter()
+            .filter(|contract| !Self::resolves(&crates, &contract.fqn))
+            .cloned()
+            .collect();
+
+        println!(
+            "{}",
+            toml::to_string(&OdraToml {
+                contracts: discovered,
+                location: PathBuf::new(),
+            })
+            .unwrap()
+        );
+        missing
+            .iter()
+            .for_each(|contract| log::info(format!("Not in Odra.toml: {}", contract.fqn)));
+        stale.iter().for_each(|contract| {
+            log::warn(format!(
+                "Odra.toml entry does not resolve to a module: {}",
+                contract.fqn
+            ))
+        });
+
+        if self.write && !missing.is_empty() {
+            odra_toml.contracts.extend(missing);
+            odra_toml.save();
+            log::info("Added missing contracts to Odra.toml.");
+        }
+    }
+
+    /// Scans every crate of the project. In a workspace, crates are prefixed with their names,
+    /// taken from their packages.
+    fn scan_crates(&self) -> Vec<(Option<String>, CrateSources)> {
+        let cargo_toml = load_cargo_toml(&self.project_root.join("Cargo.toml"));
+        let crates = match cargo_toml.workspace {
+            None => vec![(None, self.project_root.clone())],
+            Some(workspace) => workspace
+                .members
+                .iter()
+                .flat_map(|member| {
+                    let pattern = self.project_root.join(member);
+                    glob::glob(&pattern.to_string_lossy())
+                        .map(|paths| paths.filter_map(Result::ok).collect())
+                        .unwrap_or_else(|_| vec![])
+                })
+                .filter(|root| root.join("src").join("lib.rs").exists())
+                .map(|root| {
+                    let path = root.strip_prefix(&self.project_root).unwrap();
+                    let name = Project::member_crate_name(&root, &path.to_string_lossy());
+                    (Some(name), root)
+                })
+                .collect(),
+        };
+
+        crates
+            .into_iter()
+            .map(|(crate_name, root)| {
+                let sources = CrateSources::scan(&root).unwrap_or_else(|err| err.print_and_die());
+                (crate_name, sources)
+            })
+            .collect()
+    }
+
+    /// Checks if a fqn points to a module struct found in the sources.
+    fn resolves(crates: &[(Option<String>, CrateSources)], fqn: &str) -> bool {
+        let segments: Vec<String> = fqn.split_terminator("::").map(String::from).collect();
+        let Some((struct_name, module_path)) = segments.split_last() else {
+            return false;
+        };
+        crates.iter().any(|(crate_name, sources)| match crate_name {
+            None => sources.contains(module_path, struct_name),
+            Some(crate_name) => {
+                module_path.first() == Some(crate_name)
+                    && sources.contains(&module_path[1..], struct_name)
+            }
+        })
+    }
+}
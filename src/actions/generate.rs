@@ -1,15 +1,22 @@
 //! Module responsible for generating contracts code for user.
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
+use proc_macro2::LineColumn;
+use regex::Regex;
+use syn::{spanned::Spanned, Item, MacroDelimiter, Meta};
+
+use super::utils;
 use crate::{
     command,
-    consts::ODRA_TEMPLATE_GH_RAW_REPO,
     errors::Error,
+    http,
+    lib_rs::{self, LibRs},
     log,
     odra_toml::Contract,
     paths::{to_camel_case, to_snake_case},
     project::Project,
+    sources,
     template::TemplateGenerator,
 };
 
@@ -26,11 +33,12 @@ pub struct GenerateAction<'a> {
 /// GenerateAction implementation.
 impl<'a> GenerateAction<'a> {
     /// Crate a new GenerateAction for a given contract.
-    pub fn new(project: &'a Project, contract_name: String, module_name: Option<String>) -> Self {
-        if project.is_workspace() && module_name.is_none() {
-            Error::ModuleNotProvided.print_and_die();
-        }
-
+    pub fn new(
+        project: &'a Project,
+        contract_name: String,
+        module_name: Option<String>,
+        offline: bool,
+    ) -> Self {
         GenerateAction {
             project,
             contract_name: contract_name.clone(),
@@ -38,8 +46,10 @@ impl<'a> GenerateAction<'a> {
             module_root: project.module_root(module_name.clone()),
             module_name,
             template_generator: TemplateGenerator::new(
-                ODRA_TEMPLATE_GH_RAW_REPO.to_string(),
+                http::template_raw_repo(),
                 project.project_odra_location(),
+                project.project_root(),
+                offline,
             ),
         }
     }
@@ -48,12 +58,166 @@ impl<'a> GenerateAction<'a> {
 impl GenerateAction<'_> {
     /// Main function that runs the generation action.
     pub fn generate_contract(&self) {
+        self.assert_module_provided();
         log::info(format!("Adding new contract: {} ...", self.contract_name()));
-        self.add_contract_file_to_src();
+        let contract_body = self
+            .template_generator
+            .module_template(&self.contract_struct_name())
+            .unwrap_or_else(|err| err.print_and_die());
+        self.add_contract_file_to_src(&contract_body);
         self.update_lib_rs();
         self.update_odra_toml();
     }
 
+    /// Generates a non-deployable module, which is not added to Odra.toml.
+    pub fn generate_module(&self) {
+        self.assert_module_provided();
+        log::info(format!("Adding new module: {} ...", self.contract_name()));
+        let module_body = self
+            .template_generator
+            .submodule_template(&self.contract_struct_name())
+            .unwrap_or_else(|err| err.print_and_die());
+        self.add_contract_file_to_src(&module_body);
+        self.update_lib_rs();
+    }
+
+    /// Generates an error enum in its own file.
+    pub fn generate_error(&self) {
+        self.assert_module_provided();
+        log::info(format!("Adding new error: {} ...", self.contract_name()));
+        let error_body = self
+            .template_generator
+            .error_template(&self.contract_struct_name())
+            .unwrap_or_else(|err| err.print_and_die());
+        self.add_contract_file_to_src(&error_body);
+        self.insert_into_lib_rs(&format!("pub mod {};", self.module_name()));
+    }
+
+    /// Generates an event and registers it in the given contract.
+    pub fn generate_event(&self, contract_name: &str) {
+        let contract = utils::find_contract(self.project, contract_name, None);
+        let event_name = self.contract_struct_name();
+        log::info(format!(
+            "Adding new event: {} to {} ...",
+            event_name,
+            contract.struct_name()
+        ));
+
+        let path = contract.source_file(self.project);
+        let content = command::read_file_content(path.clone()).unwrap_or_else(|_| {
+            Error::ModuleNotFound(contract.module_path(self.project).join("::")).print_and_die()
+        });
+        if Regex::new(&format!(r"\b(struct|enum)\s+{}\b", event_name))
+            .unwrap()
+            .is_match(&content)
+        {
+            Error::ItemAlreadyExists(event_name, path).print_and_die();
+        }
+
+        let event_body = self
+            .template_generator
+            .event_template(&event_name)
+            .unwrap_or_else(|err| err.print_and_die());
+        let content = Self::register_event(&path, &content, &contract.struct_name(), &event_name);
+        command::write_to_file(path.clone(), &format!("{content}\n{event_body}"));
+        log::info(format!("Added {} to {}.", event_name, path.display()));
+    }
+
+    /// Generates a test module for an existing contract.
+    pub fn generate_test(&self) {
+        let contract = utils::find_contract(self.project, self.contract_name(), None);
+        log::info(format!("Adding tests for {} ...", contract.struct_name()));
+
+        let path = contract.source_file(self.project);
+        let content = command::read_file_content(path.clone()).unwrap_or_else(|_| {
+            Error::ModuleNotFound(contract.module_path(self.project).join("::")).print_and_die()
+        });
+        if Regex::new(r"\bmod\s+tests\b").unwrap().is_match(&content) {
+            Error::ItemAlreadyExists("Module tests".to_string(), path).print_and_die();
+        }
+
+        let test_body = self
+            .template_generator
+            .test_template(
+                &contract.struct_name(),
+                &to_snake_case(contract.struct_name()),
+            )
+            .unwrap_or_else(|err| err.print_and_die());
+        command::append_file(path.clone(), &format!("\n{test_body}"));
+        log::info(format!("Added tests to {}.", path.display()));
+    }
+
+    /// In a workspace, code can be generated only in one of the members.
+    fn assert_module_provided(&self) {
+        if self.project.is_workspace() && self.module_name.is_none() {
+            Error::ModuleNotProvided.print_and_die();
+        }
+    }
+
+    /// Adds an event to the `events` list of the contract's `#[odra::module]` attribute.
+    fn register_event(
+        path: &Path,
+        content: &str,
+        contract_struct_name: &str,
+        event_name: &str,
+    ) -> String {
+        let file = lib_rs::parse(path, content).unwrap_or_else(|err| err.print_and_die());
+        let attribute = file
+            .items
+            .iter()
+            .find_map(|item| match item {
+                Item::Struct(item_struct) if item_struct.ident == contract_struct_name => {
+                    item_struct
+                        .attrs
+                        .iter()
+                        .find(|attr| sources::is_odra_module(attr))
+                }
+                _ => None,
+            })
+            .unwrap_or_else(|| {
+                Error::ModuleAttributeNotFound(contract_struct_name.to_string()).print_and_die()
+            });
+
+        let start = offset(content, attribute.span().start());
+        let end = offset(content, attribute.span().end());
+        let args = match &attribute.meta {
+            Meta::List(list) => {
+                let MacroDelimiter::Paren(paren) = &list.delimiter else {
+                    Error::ModuleAttributeNotFound(contract_struct_name.to_string())
+                        .print_and_die()
+                };
+                let span = paren.span.join();
+                Some(&content[offset(content, span.start()) + 1..offset(content, span.end()) - 1])
+            }
+            _ => None,
+        };
+
+        let events = Regex::new(r"events\s*=\s*\[(?P<list>[^\]]*)\]").unwrap();
+        let args = match args {
+            None => format!("events = [{event_name}]"),
+            Some(args) => match events.captures(args) {
+                None => format!("{args}, events = [{event_name}]"),
+                Some(list) => {
+                    let list = list["list"].trim().trim_end_matches(',');
+                    let list = match list.is_empty() {
+                        true => event_name.to_string(),
+                        false => format!("{list}, {event_name}"),
+                    };
+                    events
+                        .replace(args, format!("events = [{list}]").as_str())
+                        .to_string()
+                }
+            },
+        };
+
+        format!(
+            "{}#[odra::module({})]{}",
+            &content[..start],
+            args,
+            &content[end..]
+        )
+    }
+
     /// Returns the contract name.
     fn contract_name(&self) -> &str {
         &self.contract_name
@@ -70,11 +234,6 @@ impl GenerateAction<'_> {
         to_snake_case(self.contract_name())
     }
 
-    /// Returns the module Ref identifier.
-    fn module_ref_ident(&self) -> String {
-        format!("{}Ref", self.contract_module_ident)
-    }
-
     /// Returns a path to file with contract definition.
     fn module_file_path(&self) -> PathBuf {
         self.module_root
@@ -84,13 +243,7 @@ impl GenerateAction<'_> {
     }
 
     /// Crates a new module file in src directory.
-    fn add_contract_file_to_src(&self) {
-        // Rename module name.
-        let contract_body = self
-            .template_generator
-            .module_template(&self.contract_struct_name())
-            .unwrap_or_else(|err| err.print_and_die());
-
+    fn add_contract_file_to_src(&self, contract_body: &str) {
         // Make sure the file do not exist.
         let path = self.module_file_path();
         if path.exists() {
@@ -98,10 +251,10 @@ impl GenerateAction<'_> {
         }
 
         // Write to file.
-        command::write_to_file(path, &contract_body);
+        command::write_to_file(path, contract_body);
     }
 
-    /// Append `mod` section to lib.rs.
+    /// Adds `mod` section to lib.rs.
     fn update_lib_rs(&self) {
         // Prepare code to add.
         let register_module_code = self
@@ -109,33 +262,23 @@ impl GenerateAction<'_> {
             .register_module_snippet(&self.module_name(), &self.contract_struct_name())
             .unwrap_or_else(|err| err.print_and_die());
 
-        // Read the file.
-        let lib_rs_path = self.module_root.join("src/lib.rs");
-        let lib_rs = command::read_file_content(lib_rs_path)
-            .unwrap_or_else(|_| Error::LibRsNotFound.print_and_die());
+        self.insert_into_lib_rs(&register_module_code);
+    }
+
+    /// Inserts code into lib.rs, unless the module is already declared there.
+    fn insert_into_lib_rs(&self, code: &str) {
+        let mut lib_rs = LibRs::load(&self.module_root.join("src/lib.rs"));
 
         // If the file already has module registered, throw an error.
-        if lib_rs.contains(&register_module_code) {
+        if lib_rs.has_module(&self.module_name()) {
             Error::ModuleAlreadyInLibRs(String::from(self.contract_name())).print_and_die();
         }
 
-        // Check if he file might have the module registered in another form.
-        if lib_rs.contains(self.contract_name())
-            || (lib_rs.contains(&self.contract_struct_name())
-                && lib_rs.contains(&self.module_ref_ident()))
-        {
-            log::warn(format!(
-                "src/lib.rs probably already has {} enabled. Skipping.",
-                self.contract_name()
-            ));
-            return;
-        }
-
-        // Write to file.
-        command::append_file(self.module_root.join("src/lib.rs"), &register_module_code);
+        let added_code = lib_rs.insert(code);
+        lib_rs.save();
 
         // Print info.
-        log::info(format!("Added to src/lib.rs:\n{register_module_code}"));
+        log::info(format!("Added to src/lib.rs:\n{added_code}"));
     }
 
     /// Add contract definition to Odra.toml.
@@ -164,7 +307,7 @@ impl GenerateAction<'_> {
         };
 
         // Add contract to Odra.toml.
-        odra_toml.contracts.push(Contract { fqn });
+        odra_toml.contracts.push(Contract::new(fqn));
 
         // Write to file.
         odra_toml.save();
@@ -173,3 +316,18 @@ impl GenerateAction<'_> {
         log::info("Added contract to Odra.toml.");
     }
 }
+
+/// Converts a line and column of a span into a byte offset in the content.
+fn offset(content: &str, position: LineColumn) -> usize {
+    let line_start: usize = content
+        .split_inclusive('\n')
+        .take(position.line - 1)
+        .map(str::len)
+        .sum();
+    line_start
+        + content[line_start..]
+            .chars()
+            .take(position.column)
+            .map(char::len_utf8)
+            .sum::<usize>()
+}
@@ -1,20 +1,30 @@
 //! Module responsible for initializing an Odra project.
 
-use std::path::{Path, PathBuf};
+use std::{
+    path::{Path, PathBuf},
+    str::FromStr,
+};
 
 use cargo_generate::{GenerateArgs, TemplatePath, Vcs};
 use cargo_toml::{Dependency, DependencyDetail};
 use chrono::Utc;
-use ureq::serde_json;
 
 use crate::{
     cli::InitCommand,
-    command::{rename_file, replace_in_file},
-    consts::{ODRA_GITHUB_API_DATA, ODRA_TEMPLATE_GH_REPO},
+    command::{self, rename_file, replace_in_file},
+    consts::{
+        ODRA_PROJECT_TEMPLATES_DIR,
+        ODRA_TEMPLATE_CARGO_TOML,
+        ODRA_TEMPLATE_DEPENDENCY_PLACEHOLDERS,
+        PROJECT_TEMPLATE_METADATA_FILE,
+    },
     errors::Error,
+    http,
     log,
+    odra_version,
     paths,
-    project::OdraLocation,
+    project::{GitReference, OdraLocation},
+    template_tree::TemplateTree,
 };
 
 /// InitAction configuration.
@@ -29,56 +39,72 @@ impl InitAction {
         }
 
         log::info("Generating a new project...");
+        let name =
+            paths::to_snake_case(init_command.name.as_deref().unwrap_or_else(|| {
+                Error::FailedToParseArgument("name".to_string()).print_and_die()
+            }));
 
-        let odra_location = Self::odra_location(init_command.source);
-
-        let template_path = match odra_location.clone() {
-            OdraLocation::Local(local_path) => TemplatePath {
-                auto_path: Some(local_path.as_os_str().to_str().unwrap().to_string()),
-                subfolder: Some(format!("templates/{}", init_command.template)),
-                test: false,
-                git: None,
-                branch: None,
-                tag: None,
-                path: None,
-                favorite: None,
-            },
-            OdraLocation::Remote(repo, branch) => TemplatePath {
-                auto_path: Some(repo),
-                subfolder: Some(format!("templates/{}", init_command.template)),
-                test: false,
-                git: None,
-                branch,
-                tag: None,
-                path: None,
-                favorite: None,
-            },
-            OdraLocation::CratesIO(version) => TemplatePath {
-                auto_path: Some(ODRA_TEMPLATE_GH_REPO.to_string()),
-                subfolder: Some(format!("templates/{}", init_command.template)),
-                test: false,
-                git: None,
-                branch: Some(format!("release/{}", version)),
-                tag: None,
-                path: None,
-                favorite: None,
-            },
+        let offline = init_command.offline;
+        let repo_uri = init_command.repo_uri;
+        let odra_location = Self::odra_location(init_command.source, repo_uri.clone(), offline);
+
+        let template_tree = TemplateTree::fetch(&odra_location, repo_uri.as_deref(), offline)
+            .unwrap_or_else(|err| err.print_and_die());
+        template_tree
+            .assert_template_exists(&init_command.template)
+            .unwrap_or_else(|err| err.print_and_die());
+        let template_path = TemplatePath {
+            auto_path: Some(template_tree.root().to_string_lossy().to_string()),
+            subfolder: Some(format!(
+                "{}/{}",
+                ODRA_PROJECT_TEMPLATES_DIR, init_command.template
+            )),
+            test: false,
+            git: None,
+            branch: None,
+            tag: None,
+            path: None,
+            favorite: None,
         };
 
+        let values_file = init_command.values_file.as_ref().map(|path| {
+            if !path.is_file() {
+                Error::FailedToParseArgument(format!("values file {} not found", path.display()))
+                    .print_and_die();
+            }
+            path.to_string_lossy().to_string()
+        });
+        let vcs = Vcs::from_str(&init_command.vcs)
+            .unwrap_or_else(|_| Error::FailedToParseArgument("vcs".to_string()).print_and_die());
+        let define = Self::template_variables(&init_command.define);
+
+        if command::is_dry_run() {
+            let destination = match init {
+                true => current_dir,
+                false => current_dir.join(&name),
+            };
+            log::info(format!(
+                "Would generate a project from the {} template in {}",
+                init_command.template,
+                destination.display()
+            ));
+            return;
+        }
+
         cargo_generate::generate(GenerateArgs {
             template_path,
             list_favorites: false,
-            name: Some(paths::to_snake_case(&init_command.name)),
+            name: Some(name.clone()),
             force: true,
             verbose: false,
-            template_values_file: None,
-            silent: false,
+            template_values_file: values_file,
+            silent: init_command.silent,
             config: None,
-            vcs: Some(Vcs::Git),
+            vcs: Some(vcs),
             lib: false,
             bin: false,
             ssh_identity: None,
-            define: vec![format!("date={}", Utc::now().format("%Y-%m-%d"))],
+            define,
             init,
             destination: None,
             force_git_init: false,
@@ -93,43 +119,55 @@ impl InitAction {
         let cargo_toml_path = match init {
             true => {
                 let mut path = current_dir;
-                path.push("_Cargo.toml");
+                path.push(ODRA_TEMPLATE_CARGO_TOML);
                 path
             }
             false => {
                 let mut path = current_dir;
-                path.push(paths::to_snake_case(&init_command.name));
-                path.push("_Cargo.toml");
+                path.push(name);
+                path.push(ODRA_TEMPLATE_CARGO_TOML);
                 path
             }
         };
 
-        Self::replace_package_placeholder(
-            init,
-            &odra_location,
-            &cargo_toml_path,
-            "#odra_dependency",
-            "odra",
-        );
-        Self::replace_package_placeholder(
-            init,
-            &odra_location,
-            &cargo_toml_path,
-            "#odra_test_dependency",
-            "odra-test",
-        );
-        Self::replace_package_placeholder(
-            init,
-            &odra_location,
-            &cargo_toml_path,
-            "#odra_build_dependency",
-            "odra-build",
-        );
+        for (placeholder, crate_name) in ODRA_TEMPLATE_DEPENDENCY_PLACEHOLDERS {
+            Self::replace_package_placeholder(
+                init,
+                &odra_location,
+                &cargo_toml_path,
+                placeholder,
+                crate_name,
+            );
+        }
 
+        let metadata_path = cargo_toml_path.with_file_name(PROJECT_TEMPLATE_METADATA_FILE);
+        if metadata_path.exists() {
+            command::rm_file(metadata_path);
+        }
         rename_file(cargo_toml_path, "Cargo.toml");
         log::info("Done!");
     }
 
+    /// Returns the template variables to define: the current date, unless it is given,
+    /// and the given ones.
+    fn template_variables(defines: &[String]) -> Vec<String> {
+        let keys: Vec<&str> = defines
+            .iter()
+            .map(|define| match define.split_once('=') {
+                Some((key, _)) if !key.trim().is_empty() => key.trim(),
+                _ => Error::FailedToParseArgument(format!("--define {define}, expected key=value"))
+                    .print_and_die(),
+            })
+            .collect();
+
+        let mut variables = vec![];
+        if !keys.contains(&"date") {
+            variables.push(format!("date={}", Utc::now().format("%Y-%m-%d")));
+        }
+        variables.extend(defines.iter().cloned());
+        variables
+    }
+
     fn replace_package_placeholder(
         init: bool,
         odra_location: &OdraLocation,
@@ -141,31 +179,66 @@ impl InitAction {
             cargo_toml_path.to_path_buf(),
             placeholder,
             format!(
-                "{} = {{ {} }}",
+                "{} = {}",
                 crate_name,
-                toml::to_string(&Self::odra_project_dependency(
-                    odra_location.clone(),
-                    crate_name,
-                    init
-                ))
-                .unwrap()
-                .trim_end()
-                .replace('\n', ", ")
+                Self::odra_dependency_toml(odra_location, crate_name, init)
             )
             .as_str(),
         );
     }
+
+    /// Returns the dependency on an Odra crate as an inline TOML table.
+    pub fn odra_dependency_toml(
+        odra_location: &OdraLocation,
+        crate_name: &str,
+        init: bool,
+    ) -> String {
+        format!(
+            "{{ {} }}",
+            toml::to_string(&Self::odra_project_dependency(
+                odra_location.clone(),
+                crate_name,
+                init
+            ))
+            .unwrap()
+            .trim_end()
+            .replace('\n', ", ")
+        )
+    }
+
+    /// Prints templates available in the given Odra source.
+    pub fn list_templates(source: Option<String>, repo_uri: Option<String>, offline: bool) {
+        let odra_location = Self::odra_location(source, repo_uri.clone(), offline);
+        let template_tree = TemplateTree::fetch(&odra_location, repo_uri.as_deref(), offline)
+            .unwrap_or_else(|err| err.print_and_die());
+        let templates = template_tree.templates();
+        if templates.is_empty() {
+            log::warn(format!(
+                "No templates found in {}.",
+                template_tree.root().display()
+            ));
+        }
+        templates.iter().for_each(|template| {
+            println!("{:<16}{}", template.name, template.description);
+        });
+    }
+
     fn assert_dir_is_empty(dir: PathBuf) {
         if dir.read_dir().unwrap().next().is_some() {
             Error::CurrentDirIsNotEmpty.print_and_die();
         }
     }
 
-    fn odra_location(source: Option<String>) -> OdraLocation {
+    /// Returns the Odra location of a source, by default the latest release.
+    pub fn odra_location(
+        source: Option<String>,
+        repo_uri: Option<String>,
+        offline: bool,
+    ) -> OdraLocation {
         let source = if let Some(source) = source {
             source
         } else {
-            Self::odra_latest_version()
+            odra_version::latest(offline).unwrap_or_else(|err| err.print_and_die())
         };
 
         // location on disk
@@ -178,30 +251,20 @@ impl InitAction {
             if version_regex.is_match(&source) {
                 OdraLocation::CratesIO(source)
             } else {
-                // branch
-                OdraLocation::Remote(ODRA_TEMPLATE_GH_REPO.to_string(), Some(source))
+                // git reference
+                OdraLocation::Remote(
+                    repo_uri.unwrap_or_else(http::template_repo),
+                    Some(GitReference::parse(&source)),
+                )
             }
         }
     }
-    fn odra_latest_version() -> String {
-        let response: serde_json::Value = ureq::get(ODRA_GITHUB_API_DATA)
-            .call()
-            .unwrap_or_else(|_| {
-                Error::FailedToFetchTemplate(ODRA_GITHUB_API_DATA.to_string()).print_and_die()
-            })
-            .into_json()
-            .unwrap_or_else(|_| {
-                Error::FailedToParseTemplate(ODRA_GITHUB_API_DATA.to_string()).print_and_die()
-            });
-        response["tag_name"].as_str().unwrap().to_string()
-    }
-
     fn odra_project_dependency(
         odra_location: OdraLocation,
         crate_name: &str,
         init: bool,
     ) -> Dependency {
-        let (version, path, git, branch) = match odra_location {
+        let (version, path, git, reference) = match odra_location {
             OdraLocation::Local(path) => {
                 let path = match init {
                     true => path,
@@ -215,9 +278,14 @@ impl InitAction {
                     .to_string();
                 (None, Some(path), None, None)
             }
-            OdraLocation::Remote(repo, branch) => match branch {
-                None => (Some(Self::odra_latest_version()), None, None, None),
-                Some(branch) => (None, None, Some(repo), Some(branch)),
+            OdraLocation::Remote(repo, reference) => match reference {
+                None => (
+                    Some(odra_version::latest(false).unwrap_or_else(|err| err.print_and_die())),
+                    None,
+                    None,
+                    None,
+                ),
+                Some(reference) => (None, None, Some(repo), Some(reference)),
             },
             OdraLocation::CratesIO(version) => (Some(version), None, None, None),
         };
@@ -229,9 +297,18 @@ impl InitAction {
             path,
             inherited: false,
             git,
-            branch,
-            tag: None,
-            rev: None,
+            branch: match &reference {
+                Some(GitReference::Branch(branch)) => Some(branch.clone()),
+                _ => None,
+            },
+            tag: match &reference {
+                Some(GitReference::Tag(tag)) => Some(tag.clone()),
+                _ => None,
+            },
+            rev: match &reference {
+                Some(GitReference::Rev(rev)) => Some(rev.clone()),
+                _ => None,
+            },
             features: vec![],
             optional: false,
             default_features: false,
@@ -0,0 +1,100 @@
+//! Module responsible for removing contracts from the project.
+
+use super::utils;
+use crate::{command, lib_rs::LibRs, log, odra_toml::Contract, paths, project::Project};
+
+/// RemoveAction configuration.
+pub struct RemoveAction<'a> {
+    project: &'a Project,
+    contract_name: String,
+    module_name: Option<String>,
+    keep_source: bool,
+}
+
+/// RemoveAction implementation.
+impl<'a> RemoveAction<'a> {
+    /// Creates a RemoveAction for a given contract.
+    pub fn new(
+        project: &'a Project,
+        contract_name: String,
+        module_name: Option<String>,
+        keep_source: bool,
+    ) -> Self {
+        RemoveAction {
+            project,
+            contract_name,
+            module_name,
+            keep_source,
+        }
+    }
+}
+
+impl RemoveAction<'_> {
+    /// Removes the contract's source, its declaration in lib.rs, Odra.toml entry and wasm files.
+    pub fn remove(&self) {
+        let contract =
+            utils::find_contract(self.project, &self.contract_name, self.module_name.clone());
+        log::info(format!("Removing contract: {} ...", contract.fqn));
+        if !self.keep_source {
+            self.remove_source(&contract);
+            self.update_lib_rs(&contract);
+        }
+        self.update_odra_toml(&contract);
+        self.remove_wasm_files(&contract);
+    }
+
+    /// Removes the file with the contract definition.
+    fn remove_source(&self, contract: &Contract) {
+        let path = contract.source_file(self.project);
+        if contract.module_path(self.project).is_empty() {
+            log::warn(format!(
+                "{} is defined in {}, remove it manually.",
+                contract.struct_name(),
+                path.display()
+            ));
+            return;
+        }
+        if path.exists() {
+            command::rm_file(path);
+        }
+    }
+
+    /// Removes `mod` and `use` items of the contract's module from lib.rs.
+    fn update_lib_rs(&self, contract: &Contract) {
+        let module_path = contract.module_path(self.project);
+        if module_path.len() != 1 {
+            log::warn(format!(
+                "{} is not declared in src/lib.rs, remove its module declaration manually.",
+                module_path.join("::")
+            ));
+            return;
+        }
+
+        let lib_rs_path = contract.crate_root(self.project).join("src/lib.rs");
+        let mut lib_rs = LibRs::load(&lib_rs_path);
+        let removed_code = lib_rs.remove_module(&module_path[0]);
+        if removed_code.is_empty() {
+            return;
+        }
+        log::info(format!("Removing from src/lib.rs:\n{removed_code}"));
+        lib_rs.save();
+    }
+
+    /// Removes the contract from Odra.toml.
+    fn update_odra_toml(&self, contract: &Contract) {
+        let mut odra_toml = self.project.odra_toml();
+        odra_toml.contracts.retain(|c| c.fqn != contract.fqn);
+        log::info("Removing contract from Odra.toml.");
+        odra_toml.save();
+    }
+
+    /// Removes wasm files from the project's and the member's wasm directories.
+    fn remove_wasm_files(&self, contract: &Contract) {
+        contract
+            .wasm_roots(self.project)
+            .iter()
+            .flat_map(|root| paths::wasm_artifacts(&contract.wasm_name(), root))
+            .filter(|path| path.exists())
+            .for_each(command::rm_file);
+    }
+}
@@ -0,0 +1,179 @@
+//! Module responsible for renaming contracts.
+
+use regex::Regex;
+
+use super::utils;
+use crate::{
+    command,
+    consts::GENERATED_IDENT_SUFFIXES,
+    errors::Error,
+    lib_rs::LibRs,
+    log,
+    odra_toml::Contract,
+    paths::{self, to_camel_case, to_snake_case},
+    project::Project,
+};
+
+/// RenameAction configuration.
+pub struct RenameAction<'a> {
+    project: &'a Project,
+    contract_name: String,
+    new_contract_name: String,
+    module_name: Option<String>,
+}
+
+/// RenameAction implementation.
+impl<'a> RenameAction<'a> {
+    /// Creates a RenameAction for a given contract.
+    pub fn new(
+        project: &'a Project,
+        contract_name: String,
+        new_contract_name: String,
+        module_name: Option<String>,
+    ) -> Self {
+        RenameAction {
+            project,
+            contract_name,
+            new_contract_name,
+            module_name,
+        }
+    }
+}
+
+impl RenameAction<'_> {
+    /// Renames the contract struct, its module file, declarations in lib.rs and Odra.toml entry,
+    /// and removes artifacts built under the old name.
+    pub fn rename(&self) {
+        let contract =
+            utils::find_contract(self.project, &self.contract_name, self.module_name.clone());
+        let new_struct_name = to_camel_case(&self.new_contract_name);
+        if self.project.odra_toml().has_contract(&new_struct_name) {
+            Error::ContractAlreadyInOdraToml(new_struct_name).print_and_die();
+        }
+
+        log::info(format!(
+            "Renaming contract: {} to {} ...",
+            contract.struct_name(),
+            new_struct_name
+        ));
+        let renames_module = self.renames_module(&contract);
+        self.update_source(&contract, &new_struct_name, renames_module);
+        self.update_lib_rs(&contract, &new_struct_name, renames_module);
+        self.update_odra_toml(&contract, &new_struct_name, renames_module);
+        self.remove_artifacts(&contract);
+    }
+
+    /// Checks if the contract has its own module, named after it the way `generate` names it.
+    /// Only such a module is renamed along with the contract.
+    fn renames_module(&self, contract: &Contract) -> bool {
+        let module_path = contract.module_path(self.project);
+        let expected_file_name = format!("{}.rs", to_snake_case(contract.struct_name()));
+        module_path.len() == 1
+            && module_path[0] == to_snake_case(contract.struct_name())
+            && contract
+                .source_file(self.project)
+                .file_name()
+                .map_or(false, |file_name| file_name == expected_file_name.as_str())
+    }
+
+    /// Renames identifiers in the contract file and the file itself.
+    fn update_source(&self, contract: &Contract, new_struct_name: &str, renames_module: bool) {
+        let path = contract.source_file(self.project);
+        let new_file_name = format!("{}.rs", to_snake_case(new_struct_name));
+        let new_path = path.with_file_name(&new_file_name);
+        if renames_module && new_path.exists() {
+            Error::FileAlreadyExists(new_path).print_and_die();
+        }
+
+        let content = command::read_file_content(path.clone()).unwrap_or_else(|_| {
+            Error::ModuleNotFound(contract.module_path(self.project).join("::")).print_and_die()
+        });
+        let content = rename_identifiers(&content, &contract.struct_name(), new_struct_name);
+        command::write_to_file(path.clone(), &content);
+
+        if renames_module {
+            log::info(format!("Renaming {} to {}", path.display(), new_file_name));
+            command::rename_file(path, &new_file_name);
+        }
+    }
+
+    /// Updates `mod` and `use` items of the contract's module in lib.rs.
+    fn update_lib_rs(&self, contract: &Contract, new_struct_name: &str, renames_module: bool) {
+        let module_path = contract.module_path(self.project);
+        if module_path.len() != 1 {
+            log::warn(format!(
+                "{} is not declared in src/lib.rs, update its uses manually.",
+                module_path.join("::")
+            ));
+            return;
+        }
+
+        let old_module = &module_path[0];
+        let new_module = to_snake_case(new_struct_name);
+        let module_regex = Regex::new(&format!(r"\b{}\b", old_module)).unwrap();
+        let mut lib_rs = LibRs::load(&contract.crate_root(self.project).join("src/lib.rs"));
+        let changed = lib_rs.edit_module(old_module, |line| {
+            let line = rename_identifiers(line, &contract.struct_name(), new_struct_name);
+            match renames_module {
+                true => module_regex
+                    .replace_all(&line, new_module.as_str())
+                    .to_string(),
+                false => line,
+            }
+        });
+        if changed {
+            lib_rs.save();
+            log::info("Updated src/lib.rs.");
+        }
+    }
+
+    /// Updates the contract's fqn in Odra.toml.
+    fn update_odra_toml(&self, contract: &Contract, new_struct_name: &str, renames_module: bool) {
+        let mut segments: Vec<String> = contract
+            .fqn
+            .split_terminator("::")
+            .map(ToString::to_string)
+            .collect();
+        let last = segments.len() - 1;
+        segments[last] = new_struct_name.to_string();
+        if renames_module {
+            segments[last - 1] = to_snake_case(new_struct_name);
+        }
+
+        let mut odra_toml = self.project.odra_toml();
+        odra_toml
+            .contracts
+            .iter_mut()
+            .filter(|c| c.fqn == contract.fqn)
+            .for_each(|c| c.fqn = segments.join("::"));
+        odra_toml.save();
+        log::info(format!("Updated Odra.toml: {}", segments.join("::")));
+    }
+
+    /// Removes wasm and schema files built under the old name.
+    fn remove_artifacts(&self, contract: &Contract) {
+        contract
+            .wasm_roots(self.project)
+            .iter()
+            .flat_map(|root| {
+                let mut artifacts = paths::wasm_artifacts(&contract.wasm_name(), root);
+                artifacts.extend(paths::schema_paths(&contract.struct_name(), root));
+                artifacts
+            })
+            .filter(|path| path.exists())
+            .for_each(command::rm_file);
+    }
+}
+
+/// Renames the contract struct and identifiers Odra generates for it.
+fn rename_identifiers(code: &str, struct_name: &str, new_struct_name: &str) -> String {
+    let regex = Regex::new(&format!(
+        r"\b{}({})?\b",
+        struct_name,
+        GENERATED_IDENT_SUFFIXES.join("|")
+    ))
+    .unwrap();
+    regex
+        .replace_all(code, format!("{}${{1}}", new_struct_name).as_str())
+        .to_string()
+}
@@ -1,20 +1,26 @@
 //! Module for generating contracts schema.
 
 use super::utils;
-use crate::{command, errors::Error, log, project::Project};
+use crate::{build_options::BuildOptions, command, errors::Error, log, project::Project};
 
 /// SchemaAction configuration.
 pub struct SchemaAction<'a> {
     project: &'a Project,
     contracts_names: Option<String>,
+    options: BuildOptions,
 }
 
 impl<'a> SchemaAction<'a> {
     /// Crate a new SchemaAction for a given configuration.
-    pub fn new(project: &'a Project, contracts_names: Option<String>) -> Self {
+    pub fn new(
+        project: &'a Project,
+        contracts_names: Option<String>,
+        options: BuildOptions,
+    ) -> Self {
         SchemaAction {
             project,
             contracts_names,
+            options,
         }
     }
 }
@@ -35,10 +41,14 @@ impl SchemaAction<'_> {
                 Error::FailedToParseArgument("contracts_names".to_string()).print_and_die()
             });
         for contract in contracts {
+            let mut args = self.options.cargo_args();
+            args.extend(contract.cargo_args(self.project));
+            args.extend_from_slice(&self.options.passthrough_args);
             command::cargo_generate_schema_files(
                 self.project.project_root(),
                 &contract.struct_name(),
                 &contract.crate_name(self.project),
+                &args,
             );
         }
     }
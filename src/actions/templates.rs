@@ -0,0 +1,27 @@
+//! Module responsible for managing the cache of Odra templates.
+
+use crate::{errors::Error, log, odra_version, template_tree::TemplateTree};
+
+/// TemplatesAction configuration.
+pub struct TemplatesAction {}
+
+/// TemplatesAction implementation.
+impl TemplatesAction {
+    /// Caches the source tree of the given Odra version, by default of the latest one,
+    /// so projects can be created offline.
+    pub fn fetch(version: Option<String>) {
+        let version = version.unwrap_or_else(|| {
+            odra_version::latest(false).unwrap_or_else(|err| err.print_and_die())
+        });
+        if semver::Version::parse(&version).is_err() {
+            Error::FailedToParseArgument(format!("version {version}")).print_and_die();
+        }
+
+        let tree =
+            TemplateTree::fetch_version(&version, false).unwrap_or_else(|err| err.print_and_die());
+        log::info(format!(
+            "Odra {version} templates are cached in {}.",
+            tree.root().display()
+        ));
+    }
+}
@@ -1,7 +1,7 @@
 //! Module responsible for running contracts tests.
 
 use super::build::BuildAction;
-use crate::{command, log, project::Project};
+use crate::{build_options::BuildOptions, command, log, project::Project};
 
 /// TestAction configuration.
 pub struct TestAction<'a> {
@@ -70,7 +70,7 @@ impl TestAction<'_> {
 
     /// Build *.wasm files before testing.
     fn build_wasm_files(&self) {
-        BuildAction::new(self.project, None).build();
+        BuildAction::new(self.project, None, false, BuildOptions::default()).build();
         log::info("Building finished.")
     }
 }
@@ -0,0 +1,308 @@
+//! Module responsible for upgrading Odra in a project.
+
+use std::path::PathBuf;
+
+use semver::{BuildMetadata, Version, VersionReq};
+use toml_edit::{value, InlineTable, Item, TableLike, Value};
+
+use super::init::InitAction;
+use crate::{
+    cargo_toml::load_cargo_toml_document,
+    command,
+    consts::{
+        CARGO_DEPENDENCIES_TABLES,
+        CARGO_DEPENDENCY_LOCATION_KEYS,
+        ODRA_CHANGELOG_FILE,
+        ODRA_CRATES,
+        ODRA_TEMPLATE_BINS,
+    },
+    errors::Error,
+    fs,
+    log,
+    odra_version,
+    paths,
+    project::{OdraLocation, Project},
+    template_tree::TemplateTree,
+};
+
+/// UpgradeAction configuration.
+pub struct UpgradeAction<'a> {
+    project: &'a Project,
+    to: Option<String>,
+    offline: bool,
+}
+
+/// UpgradeAction implementation.
+impl<'a> UpgradeAction<'a> {
+    /// Creates an UpgradeAction for a given project.
+    pub fn new(project: &'a Project, to: Option<String>, offline: bool) -> Self {
+        UpgradeAction {
+            project,
+            to,
+            offline,
+        }
+    }
+}
+
+impl UpgradeAction<'_> {
+    /// Reports whether a newer Odra release than the one used by the project exists.
+    pub fn check(&self) {
+        let current = self.project.project_odra_location();
+        let latest = odra_version::latest(self.offline).unwrap_or_else(|err| err.print_and_die());
+        let OdraLocation::CratesIO(version) = &current else {
+            log::info(format!(
+                "The project uses Odra from {current}, the latest release is {latest}."
+            ));
+            return;
+        };
+        match (parse_version(version), parse_version(&latest)) {
+            (Some(used), Some(released)) if released > used => log::info(format!(
+                "Odra {latest} is available, the project uses {version}. \
+                Run `cargo odra upgrade` to upgrade."
+            )),
+            (Some(_), Some(_)) => log::info(format!("The project uses the latest Odra {version}.")),
+            _ => log::warn(format!(
+                "Could not compare the used Odra {version} with the latest {latest}."
+            )),
+        }
+    }
+
+    /// Upgrades the Odra dependencies, refreshes the build binaries that were not modified
+    /// and prints the changelog of the new versions.
+    pub fn upgrade(&self) {
+        let current = self.project.project_odra_location();
+        let target = self.target_location();
+        log::info(format!("Upgrading Odra from {current} to {target}..."));
+        self.update_dependencies(&target);
+
+        match TemplateTree::fetch(&target, None, self.offline) {
+            Ok(tree) => {
+                self.refresh_bins(&tree, &current);
+                print_changelog(&tree, &current, &target);
+            }
+            Err(err) => log::warn(format!(
+                "{err} Build binaries are not refreshed, check them manually."
+            )),
+        }
+        log::info("Done!");
+    }
+
+    /// Returns the location to upgrade to, by default the latest release.
+    fn target_location(&self) -> OdraLocation {
+        InitAction::odra_location(self.to.clone(), None, self.offline)
+    }
+
+    /// Updates the location of the Odra crates in the project's Cargo.toml, in the workspace
+    /// dependencies or in the dependencies of the root crate. Inherited ones are left as
+    /// they are.
+    fn update_dependencies(&self, target: &OdraLocation) {
+        // A local path is written relative to the manifest, like `init` writes it.
+        let target = &match target {
+            OdraLocation::Local(odra) => {
+                OdraLocation::Local(paths::relative_path(odra, &self.project.project_root()))
+            }
+            location => location.clone(),
+        };
+        let path = &self.project.cargo_toml_location;
+        let mut cargo_toml = load_cargo_toml_document(path);
+        let mut updated = 0;
+
+        if let Some(dependencies) = cargo_toml
+            .get_mut("workspace")
+            .and_then(|workspace| workspace.get_mut("dependencies"))
+            .and_then(Item::as_table_like_mut)
+        {
+            updated += update_table(dependencies, target);
+        }
+        for kind in CARGO_DEPENDENCIES_TABLES {
+            if let Some(dependencies) = cargo_toml.get_mut(kind).and_then(Item::as_table_like_mut) {
+                updated += update_table(dependencies, target);
+            }
+        }
+        if let Some(platforms) = cargo_toml
+            .get_mut("target")
+            .and_then(Item::as_table_like_mut)
+        {
+            for (_, platform) in platforms.iter_mut() {
+                for kind in CARGO_DEPENDENCIES_TABLES {
+                    if let Some(dependencies) =
+                        platform.get_mut(kind).and_then(Item::as_table_like_mut)
+                    {
+                        updated += update_table(dependencies, target);
+                    }
+                }
+            }
+        }
+
+        if updated == 0 {
+            Error::OdraNotADependency.print_and_die();
+        }
+        fs::write(path, &cargo_toml.to_string()).unwrap_or_else(|err| err.print_and_die());
+    }
+
+    /// Replaces the build binaries with the ones of the new template. Binaries that differ
+    /// from the template of the current version were modified, so they are only reported.
+    fn refresh_bins(&self, tree: &TemplateTree, current: &OdraLocation) {
+        let current_tree = TemplateTree::fetch(current, None, self.offline).ok();
+        for (crate_root, crate_name) in self.crates() {
+            for bin in ODRA_TEMPLATE_BINS {
+                let path = crate_root.join(bin);
+                let (Ok(new), Ok(used)) = (
+                    tree.project_file(bin, &crate_name),
+                    command::read_file_content(path.clone()),
+                ) else {
+                    continue;
+                };
+                if used == new {
+                    continue;
+                }
+                let old = current_tree
+                    .as_ref()
+                    .and_then(|tree| tree.project_file(bin, &crate_name).ok());
+                if old.as_deref() == Some(used.as_str()) {
+                    log::info(format!("Updating {}", path.display()));
+                    fs::write(&path, &new).unwrap_or_else(|err| err.print_and_die());
+                } else {
+                    log::warn(format!(
+                        "{} differs from the template of the new Odra, update it manually.",
+                        path.display()
+                    ));
+                }
+            }
+        }
+    }
+
+    /// Returns roots and names of the project's Odra crates.
+    fn crates(&self) -> Vec<(PathBuf, String)> {
+        match self.project.is_workspace() {
+            true => self
+                .project
+                .members
+                .iter()
+                .map(|member| (member.root.clone(), member.name.replace('-', "_")))
+                .collect(),
+            false => vec![(
+                self.project.project_root(),
+                self.project.project_crate_name(),
+            )],
+        }
+    }
+}
+
+/// Updates the Odra crates in a dependencies table. Returns the number of updated ones.
+fn update_table(dependencies: &mut dyn TableLike, target: &OdraLocation) -> usize {
+    ODRA_CRATES
+        .iter()
+        .filter(|crate_name| {
+            let Some(dependency) = dependencies.get_mut(crate_name) else {
+                return false;
+            };
+            let inherited = dependency
+                .as_table_like()
+                .map(|table| table.contains_key("workspace"))
+                .unwrap_or(false);
+            if !inherited {
+                *dependency = upgraded_dependency(dependency, target, crate_name);
+            }
+            !inherited
+        })
+        .count()
+}
+
+/// Returns the dependency with the location keys written like `new` writes them,
+/// and the other keys, like features, as they were.
+fn upgraded_dependency(dependency: &Item, target: &OdraLocation, crate_name: &str) -> Item {
+    let shape = InitAction::odra_dependency_toml(target, crate_name, true)
+        .parse::<Value>()
+        .ok()
+        .and_then(|shape| shape.as_inline_table().cloned())
+        .unwrap_or_default();
+
+    let mut upgraded = InlineTable::new();
+    shape
+        .iter()
+        .filter(|(key, _)| CARGO_DEPENDENCY_LOCATION_KEYS.contains(key))
+        .for_each(|(key, location)| {
+            upgraded.insert(key, location.clone());
+        });
+    if let Some(table) = dependency.as_table_like() {
+        table
+            .iter()
+            .filter(|(key, _)| !CARGO_DEPENDENCY_LOCATION_KEYS.contains(key))
+            .filter_map(|(key, item)| item.as_value().map(|other| (key, other)))
+            .for_each(|(key, other)| {
+                upgraded.insert(key, other.clone());
+            });
+    }
+    upgraded.fmt();
+
+    match dependency {
+        // Keep the short form of a version only dependency.
+        Item::Value(Value::String(_)) if upgraded.len() == 1 => match upgraded.get("version") {
+            Some(version) => Item::Value(version.clone()),
+            None => value(upgraded),
+        },
+        Item::Table(table) => {
+            let mut upgraded = upgraded.into_table();
+            if let Some(position) = table.position() {
+                upgraded.set_position(position);
+            }
+            Item::Table(upgraded)
+        }
+        _ => value(upgraded),
+    }
+}
+
+/// Prints the changelog sections of the versions between the current and the target one.
+fn print_changelog(tree: &TemplateTree, current: &OdraLocation, target: &OdraLocation) {
+    let (OdraLocation::CratesIO(from), OdraLocation::CratesIO(to)) = (current, target) else {
+        return;
+    };
+    let (Some(from), Some(to)) = (parse_version(from), parse_version(to)) else {
+        return;
+    };
+    let Ok(changelog) = command::read_file_content(tree.root().join(ODRA_CHANGELOG_FILE)) else {
+        return;
+    };
+
+    let mut included = false;
+    let sections: Vec<&str> = changelog
+        .lines()
+        .filter(|line| {
+            if let Some(heading) = line.strip_prefix("## ") {
+                included = heading
+                    .trim_start_matches('[')
+                    .split([']', ' '])
+                    .next()
+                    .and_then(parse_version)
+                    .map(|version| version > from && version <= to)
+                    .unwrap_or(false);
+            }
+            included
+        })
+        .collect();
+    if !sections.is_empty() {
+        log::info(format!("Changes in Odra since {from}:"));
+        log::text(sections.join("\n"));
+    }
+}
+
+/// Parses a version of a dependency, ignoring the requirement operators. Missing parts of
+/// a partial version, like in `odra = "1"`, are zeros.
+fn parse_version(version: &str) -> Option<Version> {
+    let version = version.trim().trim_start_matches('v');
+    Version::parse(version).ok().or_else(|| {
+        let comparator = VersionReq::parse(version)
+            .ok()?
+            .comparators
+            .first()?
+            .clone();
+        Some(Version {
+            major: comparator.major,
+            minor: comparator.minor.unwrap_or(0),
+            patch: comparator.patch.unwrap_or(0),
+            pre: comparator.pre,
+            build: BuildMetadata::EMPTY,
+        })
+    })
+}
@@ -17,11 +17,43 @@ pub fn contracts(project: &Project, names_string: String) -> Result<Vec<Contract
         false => odra_toml
             .contracts
             .into_iter()
-            .filter(|c| names.contains(&c.struct_name()))
+            .filter(|c| names.iter().any(|name| c.is_named(name)))
             .collect(),
     })
 }
 
+/// Finds a contract in Odra.toml by its name, in the given module if it is set.
+pub fn find_contract(
+    project: &Project,
+    contract_name: &str,
+    module_name: Option<String>,
+) -> Contract {
+    let crate_name = module_name.map(|module_name| project.crate_name(Some(module_name)));
+    project
+        .odra_toml()
+        .contracts
+        .into_iter()
+        .filter(|contract| contract.is_named(contract_name))
+        .find(|contract| match &crate_name {
+            Some(crate_name) => contract.crate_name(project) == *crate_name,
+            None => true,
+        })
+        .unwrap_or_else(|| {
+            Error::ContractNotFound(to_camel_case(contract_name), contract_names(project))
+                .print_and_die()
+        })
+}
+
+/// Returns names of the contracts in Odra.toml, as they can be given on the command line.
+pub fn contract_names(project: &Project) -> Vec<String> {
+    project
+        .odra_toml()
+        .contracts
+        .iter()
+        .map(|contract| contract.wasm_name())
+        .collect()
+}
+
 /// Check if contract name argument is valid if set.
 pub fn validate_contract_name_argument(project: &Project, names_string: String) {
     let names = parse_contracts_names(names_string).unwrap_or_default();
@@ -30,9 +62,9 @@ pub fn validate_contract_name_argument(project: &Project, names_string: String)
             .odra_toml()
             .contracts
             .iter()
-            .any(|c| c.struct_name() == *contract_name)
+            .any(|c| c.is_named(contract_name))
         {
-            Error::ContractNotFound(contract_name.clone()).print_and_die();
+            Error::ContractNotFound(contract_name.clone(), contract_names(project)).print_and_die();
         }
     });
 }
@@ -54,7 +86,6 @@ fn parse_contracts_names(names_string: String) -> Result<Vec<String>, &'static s
             string
                 .split(' ')
                 .map(ToString::to_string)
-                .map(to_camel_case)
                 .collect::<Vec<_>>()
         }),
     }
@@ -0,0 +1,80 @@
+//! Module responsible for verifying that a wasm file can be rebuilt from the sources.
+
+use std::path::PathBuf;
+
+use super::{build::BuildAction, utils};
+use crate::{
+    build_options::BuildOptions,
+    command,
+    errors::Error,
+    log,
+    paths,
+    project::Project,
+    reproducible,
+};
+
+/// VerifyAction configuration.
+pub struct VerifyAction<'a> {
+    project: &'a Project,
+    wasm: PathBuf,
+    contract_name: Option<String>,
+}
+
+/// VerifyAction implementation.
+impl<'a> VerifyAction<'a> {
+    /// Creates a VerifyAction for a given wasm file.
+    pub fn new(project: &'a Project, wasm: PathBuf, contract_name: Option<String>) -> Self {
+        VerifyAction {
+            project,
+            wasm,
+            contract_name,
+        }
+    }
+}
+
+impl VerifyAction<'_> {
+    /// Rebuilds the contract reproducibly and compares the result with the given wasm file.
+    pub fn verify(&self) {
+        let contract_name =
+            utils::find_contract(self.project, &self.contract_name(), None).wasm_name();
+        // The given file may be the one the build overwrites, so hash it first.
+        let expected = reproducible::sha256(&self.wasm);
+
+        log::info(format!("Rebuilding {contract_name}..."));
+        BuildAction::new(
+            self.project,
+            Some(contract_name.clone()),
+            true,
+            BuildOptions::default(),
+        )
+        .build();
+
+        if command::is_dry_run() {
+            log::info("Dry run, the rebuilt wasm file is not compared.");
+            return;
+        }
+
+        let rebuilt = paths::wasm_path_in_wasm_dir(&contract_name, &self.project.project_root());
+        let actual = reproducible::sha256(&rebuilt);
+        if expected != actual {
+            Error::WasmHashMismatch(contract_name, expected, actual).print_and_die();
+        }
+
+        log::info(format!(
+            "{} matches {contract_name} built from sources ({actual}).",
+            self.wasm.display()
+        ));
+    }
+
+    /// Returns the contract name, by default derived from the wasm file name.
+    fn contract_name(&self) -> String {
+        match &self.contract_name {
+            Some(contract_name) => contract_name.clone(),
+            None => self
+                .wasm
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+                .unwrap_or_else(|| Error::WasmFileNotFound(self.wasm.clone()).print_and_die()),
+        }
+    }
+}
@@ -0,0 +1,341 @@
+//! Module responsible for creating source verification bundles.
+
+use std::{
+    collections::BTreeSet,
+    fs::File,
+    path::{Path, PathBuf},
+};
+
+use flate2::{write::GzEncoder, Compression};
+use tar::{Builder, Header, HeaderMode};
+use ureq::serde_json::{self, json, Map, Value};
+use walkdir::WalkDir;
+
+use super::{build::BuildAction, utils};
+use crate::{
+    build_options::BuildOptions,
+    command,
+    consts::{
+        ODRA_MODULE_ENV_KEY,
+        RUSTUP_TOOLCHAIN_ENV_KEY,
+        SOURCE_DATE_EPOCH_ENV_KEY,
+        VERIFICATION_BUNDLE_EXCLUDED_FILES,
+        VERIFICATION_BUNDLE_PACKAGE_ENTRIES,
+        VERIFICATION_BUNDLE_VENDOR_DIR,
+        VERIFICATION_DESCRIPTOR_FILE,
+    },
+    errors::Error,
+    fs,
+    log,
+    odra_toml::Contract,
+    paths,
+    project::Project,
+    reproducible,
+};
+
+/// VerifyBundleAction configuration.
+pub struct VerifyBundleAction<'a> {
+    project: &'a Project,
+    contract_name: String,
+}
+
+/// VerifyBundleAction implementation.
+impl<'a> VerifyBundleAction<'a> {
+    /// Creates a VerifyBundleAction for a given contract.
+    pub fn new(project: &'a Project, contract_name: String) -> Self {
+        VerifyBundleAction {
+            project,
+            contract_name,
+        }
+    }
+}
+
+impl VerifyBundleAction<'_> {
+    /// Builds the contract reproducibly and packs everything needed to rebuild it offline.
+    pub fn create_bundle(&self) {
+        let contract = utils::find_contract(self.project, &self.contract_name, None);
+        BuildAction::new(
+            self.project,
+            Some(self.contract_name.clone()),
+            true,
+            BuildOptions::default(),
+        )
+        .build();
+
+        let bundle_path =
+            paths::verification_bundle_path(&contract.wasm_name(), &self.project.project_root());
+        if command::is_dry_run() {
+            log::info(format!("Would create {}", bundle_path.display()));
+            return;
+        }
+        log::info(format!("Creating {}...", bundle_path.display()));
+        let metadata = self.metadata();
+        let vendor = self.vendor();
+        self.write_bundle(&bundle_path, &contract, &metadata, &vendor)
+            .unwrap_or_else(|err| Error::FailedToCreateBundle(err.to_string()).print_and_die());
+        log::info("Done!");
+    }
+
+    fn write_bundle(
+        &self,
+        bundle_path: &Path,
+        contract: &Contract,
+        metadata: &Metadata,
+        vendor: &Vendor,
+    ) -> std::io::Result<()> {
+        let project_root = self.project.project_root();
+        let encoder = GzEncoder::new(File::create(bundle_path)?, Compression::default());
+        let mut archive = Builder::new(encoder);
+        archive.mode(HeaderMode::Deterministic);
+
+        for path in self.source_files(&metadata.packages) {
+            let relative_path = path.strip_prefix(&project_root).unwrap();
+            log::info(format!("Adding {}", relative_path.display()));
+            archive.append_path_with_name(&path, relative_path)?;
+        }
+        log::info(format!("Adding {VERIFICATION_BUNDLE_VENDOR_DIR}"));
+        for path in files_in(&vendor.dir) {
+            let relative_path = Path::new(VERIFICATION_BUNDLE_VENDOR_DIR)
+                .join(path.strip_prefix(&vendor.dir).unwrap());
+            archive.append_path_with_name(&path, relative_path)?;
+        }
+
+        let descriptor =
+            serde_json::to_vec_pretty(&self.descriptor(contract, metadata, vendor)).unwrap();
+        let mut header = Header::new_gnu();
+        header.set_size(descriptor.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        archive.append_data(
+            &mut header,
+            VERIFICATION_DESCRIPTOR_FILE,
+            descriptor.as_slice(),
+        )?;
+
+        archive.into_inner()?.finish()?;
+        Ok(())
+    }
+
+    /// Returns files needed to rebuild the contract: the files configuring the build and
+    /// the sources of the local packages - the project's crates and their path dependencies.
+    /// Only the entries `cargo package` would take are included, so local files like `.env`
+    /// do not leak into the bundle.
+    fn source_files(&self, packages: &[PathBuf]) -> BTreeSet<PathBuf> {
+        let project_root = self.project.project_root();
+        let mut files: BTreeSet<PathBuf> = ["Cargo.toml", "Cargo.lock", "Odra.toml"]
+            .iter()
+            .map(|file| project_root.join(file))
+            .filter(|path| path.exists())
+            .collect();
+        files.extend(reproducible::toolchain_file(&project_root));
+        files.extend(
+            files_in(&project_root.join(".cargo"))
+                .into_iter()
+                .filter(|path| {
+                    !VERIFICATION_BUNDLE_EXCLUDED_FILES
+                        .iter()
+                        .any(|excluded| path.file_name() == Some(excluded.as_ref()))
+                }),
+        );
+
+        for package in packages {
+            if !package.starts_with(&project_root) {
+                log::warn(format!(
+                    "{} is outside of the project, it is not included in the bundle.",
+                    package.display()
+                ));
+                continue;
+            }
+            VERIFICATION_BUNDLE_PACKAGE_ENTRIES
+                .iter()
+                .for_each(|entry| files.extend(files_in(&package.join(entry))));
+        }
+        files
+    }
+
+    /// Reads the local packages and the registry sources directory from `cargo metadata`.
+    fn metadata(&self) -> Metadata {
+        let output = command::cargo_output(
+            &self.project.project_root(),
+            &["metadata", "--format-version", "1", "--locked"],
+        );
+        let metadata: Value = serde_json::from_str(&output).unwrap_or_else(|err| {
+            Error::FailedToCreateBundle(format!("invalid cargo metadata ({err})")).print_and_die()
+        });
+        let packages = metadata["packages"].as_array().cloned().unwrap_or_default();
+        let manifest_dir = |package: &Value| {
+            package["manifest_path"]
+                .as_str()
+                .and_then(|path| Path::new(path).parent())
+                .map(Path::to_path_buf)
+        };
+
+        let cargo_home = home::cargo_home().unwrap_or_default();
+        let registry_src = packages
+            .iter()
+            .filter(|package| {
+                package["source"]
+                    .as_str()
+                    .map(|source| source.starts_with("registry+"))
+                    .unwrap_or(false)
+            })
+            .find_map(|package| {
+                let registry_dir = manifest_dir(package)?.parent()?.to_path_buf();
+                let relative = registry_dir.strip_prefix(&cargo_home).ok()?;
+                Some(relative.to_string_lossy().to_string())
+            });
+        Metadata {
+            packages: packages
+                .iter()
+                .filter(|package| package["source"].is_null())
+                .filter_map(manifest_dir)
+                .collect(),
+            registry_src,
+        }
+    }
+
+    /// Vendors the dependencies, so the bundle can be rebuilt offline.
+    fn vendor(&self) -> Vendor {
+        let project_root = self.project.project_root();
+        let dir = paths::build_target_dir(&project_root)
+            .join("odra")
+            .join(VERIFICATION_BUNDLE_VENDOR_DIR);
+        fs::remove_dir(&dir).unwrap_or_else(|err| err.print_and_die());
+        log::info("Vendoring dependencies...");
+        let output = command::cargo_output(
+            &project_root,
+            &[
+                "vendor",
+                "--locked",
+                "--versioned-dirs",
+                &dir.to_string_lossy(),
+            ],
+        );
+        let config: toml::Table = toml::from_str(&output).unwrap_or_else(|err| {
+            Error::FailedToCreateBundle(format!("invalid cargo vendor config ({err})"))
+                .print_and_die()
+        });
+        Vendor {
+            dir,
+            config_args: vendor_config_args(&config),
+        }
+    }
+
+    /// Describes how the contract was built and what the result should be.
+    fn descriptor(&self, contract: &Contract, metadata: &Metadata, vendor: &Vendor) -> Value {
+        let project_root = self.project.project_root();
+        let crate_name = contract.crate_name(self.project);
+        let build_info = command::read_file_content(paths::build_info_path(
+            &contract.wasm_name(),
+            &project_root,
+        ))
+        .ok()
+        .and_then(|content| serde_json::from_str::<Value>(&content).ok())
+        .unwrap_or_default();
+
+        let mut env = Map::new();
+        env.insert(
+            ODRA_MODULE_ENV_KEY.to_string(),
+            json!(contract.struct_name()),
+        );
+        env.insert(
+            RUSTUP_TOOLCHAIN_ENV_KEY.to_string(),
+            json!(reproducible::toolchain(&project_root)),
+        );
+        env.insert(
+            SOURCE_DATE_EPOCH_ENV_KEY.to_string(),
+            build_info["source_date_epoch"].clone(),
+        );
+
+        let options = BuildOptions::default();
+        let mut command = vec!["cargo".to_string(), "build".to_string()];
+        let mut args = options.cargo_args();
+        args.extend(contract.cargo_args(self.project));
+        args.extend(reproducible::portable_cargo_args(
+            metadata.registry_src.as_deref(),
+        ));
+        args.push("--offline".to_string());
+        args.extend(vendor.config_args.iter().cloned());
+        command.extend(command::wasm_build_args(&crate_name, &args));
+
+        let artifact = paths::wasm_path_in_target(
+            &format!("{crate_name}_build_contract"),
+            project_root.clone(),
+            &options,
+        );
+        let artifact = artifact
+            .strip_prefix(&project_root)
+            .unwrap_or(&artifact)
+            .to_string_lossy()
+            .to_string();
+
+        json!({
+            "contract": contract.wasm_name(),
+            "fqn": contract.fqn,
+            "crate": crate_name,
+            "env": env,
+            "command": command,
+            "artifact": artifact,
+            "post_processing": [
+                ["wasm-opt", "--signext-lowering", &artifact, "-o", &artifact],
+                ["wasm-strip", &artifact],
+            ],
+            "sha256": build_info["sha256"],
+            "tools": build_info["tools"],
+        })
+    }
+}
+
+/// Local packages of the project and where the registry dependencies were built from.
+struct Metadata {
+    /// Roots of the packages built from local paths.
+    packages: Vec<PathBuf>,
+    /// Registry sources directory, relative to the cargo home.
+    registry_src: Option<String>,
+}
+
+/// Vendored dependencies.
+struct Vendor {
+    /// Directory the dependencies are vendored to.
+    dir: PathBuf,
+    /// `--config` args replacing the dependency sources with the bundled vendor directory.
+    config_args: Vec<String>,
+}
+
+/// Turns the source replacement printed by `cargo vendor` into `--config` args, pointing
+/// to the vendor directory of the bundle.
+fn vendor_config_args(config: &toml::Table) -> Vec<String> {
+    let Some(sources) = config.get("source").and_then(toml::Value::as_table) else {
+        return vec![];
+    };
+    sources
+        .iter()
+        .flat_map(|(name, source)| {
+            let name = toml::Value::String(name.clone()).to_string();
+            source
+                .as_table()
+                .into_iter()
+                .flatten()
+                .map(move |(key, value)| {
+                    let value = match key.as_str() {
+                        "directory" => {
+                            toml::Value::String(VERIFICATION_BUNDLE_VENDOR_DIR.to_string())
+                        }
+                        _ => value.clone(),
+                    };
+                    format!("source.{name}.{key}={value}")
+                })
+        })
+        .flat_map(|config| ["--config".to_string(), config])
+        .collect()
+}
+
+/// Returns all files in a directory.
+fn files_in(dir: &Path) -> Vec<PathBuf> {
+    WalkDir::new(dir)
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_file())
+        .map(|entry| entry.into_path())
+        .collect()
+}
@@ -0,0 +1,408 @@
+//! Module responsible for managing workspaces of Odra projects.
+
+use std::path::{Component, Path, PathBuf};
+
+use toml_edit::{value, Array, Decor, Document, InlineTable, Item, Table, TableLike};
+
+use super::generate::GenerateAction;
+use crate::{
+    cargo_toml::{load_cargo_toml, load_cargo_toml_document},
+    command,
+    consts::{
+        CARGO_DEPENDENCIES_TABLES,
+        CARGO_WORKSPACE_ROOT_TABLES,
+        ODRA_CRATES,
+        ODRA_TEMPLATE_BINS,
+        ODRA_TEMPLATE_CARGO_TOML,
+        ODRA_TEMPLATE_DEPENDENCY_PLACEHOLDERS,
+        WORKSPACE_ROOT_ENTRIES,
+    },
+    errors::Error,
+    fs,
+    log,
+    paths,
+    project::Project,
+    template_tree::TemplateTree,
+};
+
+/// WorkspaceAction configuration.
+pub struct WorkspaceAction<'a> {
+    project: &'a Project,
+}
+
+/// WorkspaceAction implementation.
+impl<'a> WorkspaceAction<'a> {
+    /// Creates a WorkspaceAction for a given project.
+    pub fn new(project: &'a Project) -> Self {
+        WorkspaceAction { project }
+    }
+}
+
+impl WorkspaceAction<'_> {
+    /// Converts a single crate project into a workspace with the crate as its only member.
+    ///
+    /// The crate is moved into a directory named after it, the Odra crates become
+    /// workspace dependencies, and the fqns in Odra.toml get the crate name as a prefix.
+    /// The wasm files are copied to the member, like `build` does it for workspaces.
+    pub fn convert(&self) {
+        let root = self.project.project_root();
+        if self.project.is_workspace()
+            || load_cargo_toml(&self.project.cargo_toml_location)
+                .workspace
+                .is_some()
+        {
+            Error::AlreadyAWorkspace(root).print_and_die();
+        }
+
+        let member = self.project.project_crate_name();
+        let member_root = root.join(&member);
+        if member_root.exists() {
+            Error::ItemAlreadyExists(member, root).print_and_die();
+        }
+
+        log::info(format!(
+            "Converting the project into a workspace with {member} member..."
+        ));
+        let mut member_toml = load_cargo_toml_document(&self.project.cargo_toml_location);
+        let workspace_toml = Self::split_cargo_toml(&mut member_toml, &member);
+
+        let entries = Self::entries_to_move(&root);
+        fs::create_dir(&member_root).unwrap_or_else(|err| err.print_and_die());
+        entries.iter().for_each(|entry| {
+            let target = member_root.join(entry.file_name().unwrap_or_default());
+            fs::rename(entry, &target).unwrap_or_else(|err| err.print_and_die());
+        });
+        fs::write(&member_root.join("Cargo.toml"), &member_toml.to_string())
+            .unwrap_or_else(|err| err.print_and_die());
+        fs::write(
+            &self.project.cargo_toml_location,
+            &workspace_toml.to_string(),
+        )
+        .unwrap_or_else(|err| err.print_and_die());
+
+        self.copy_wasm_files(&member_root);
+        self.update_odra_toml(&member);
+        log::info("Done!");
+    }
+
+    /// Adds a new Odra crate to the workspace, optionally with a first contract. The crate
+    /// is made of the default project template of the Odra version used by the workspace.
+    pub fn add_member(&self, name: &str, contract_name: Option<String>, offline: bool) {
+        let root = self.project.project_root();
+        let cargo_toml_location = &self.project.cargo_toml_location;
+        if load_cargo_toml(cargo_toml_location).workspace.is_none() {
+            Error::NotAWorkspace(root).print_and_die();
+        }
+
+        let member = paths::to_snake_case(name);
+        let member_root = root.join(&member);
+        if member_root.exists() {
+            Error::ItemAlreadyExists(member, root).print_and_die();
+        }
+
+        log::info(format!("Adding {member} member..."));
+        let tree = TemplateTree::fetch(&self.project.project_odra_location(), None, offline)
+            .unwrap_or_else(|err| err.print_and_die());
+        let files = Self::member_files(&tree, &member).unwrap_or_else(|err| err.print_and_die());
+
+        for dir in ["src", "bin"] {
+            fs::create_dir(&member_root.join(dir)).unwrap_or_else(|err| err.print_and_die());
+        }
+        files.iter().for_each(|(file, content)| {
+            fs::write(&member_root.join(file), content).unwrap_or_else(|err| err.print_and_die());
+        });
+
+        // Registered last, so a failure above does not leave a workspace that does not load.
+        let mut workspace_toml = load_cargo_toml_document(cargo_toml_location);
+        Self::register_member(&mut workspace_toml, &member);
+        fs::write(cargo_toml_location, &workspace_toml.to_string())
+            .unwrap_or_else(|err| err.print_and_die());
+
+        if let Some(contract_name) = contract_name {
+            self.generate_contract(&member, contract_name, offline);
+        }
+        log::info("Done!");
+    }
+
+    /// Renders files of a new member from the default project template: the manifest with
+    /// the Odra crates inherited from the workspace, src/lib.rs without the template's
+    /// modules, the build script and the build binaries.
+    fn member_files(tree: &TemplateTree, member: &str) -> Result<Vec<(String, String)>, Error> {
+        let lib_rs: String = tree
+            .project_file("src/lib.rs", member)?
+            .lines()
+            .filter(|line| {
+                let line = line.trim_start();
+                line.starts_with("#![") || line.starts_with("extern crate")
+            })
+            .map(|line| format!("{line}\n"))
+            .collect();
+        let mut files = vec![
+            (
+                "Cargo.toml".to_string(),
+                Self::member_cargo_toml(tree, member)?,
+            ),
+            ("src/lib.rs".to_string(), lib_rs),
+        ];
+        for file in ["build.rs"].iter().chain(ODRA_TEMPLATE_BINS.iter()) {
+            if let Ok(content) = tree.project_file(file, member) {
+                files.push((file.to_string(), content));
+            }
+        }
+        Ok(files)
+    }
+
+    /// Renders the manifest of a new member. Tables only the workspace root may have, like
+    /// profiles, are left out.
+    fn member_cargo_toml(tree: &TemplateTree, member: &str) -> Result<String, Error> {
+        let content = ODRA_TEMPLATE_DEPENDENCY_PLACEHOLDERS.iter().fold(
+            tree.project_file(ODRA_TEMPLATE_CARGO_TOML, member)?,
+            |content, (placeholder, crate_name)| {
+                content.replace(
+                    placeholder,
+                    &format!("{crate_name} = {{ workspace = true }}"),
+                )
+            },
+        );
+        let mut cargo_toml = content.parse::<Document>().map_err(|err| {
+            Error::FailedToParseTemplate(format!("{ODRA_TEMPLATE_CARGO_TOML}: {err}"))
+        })?;
+        for table in CARGO_WORKSPACE_ROOT_TABLES {
+            cargo_toml.remove(table);
+        }
+        Ok(cargo_toml.to_string())
+    }
+
+    /// Adds the member to `workspace.members` and makes sure the Odra crates it inherits
+    /// are workspace dependencies. The missing ones get the location of `odra`.
+    fn register_member(workspace_toml: &mut Document, member: &str) {
+        let members = &mut workspace_toml["workspace"]["members"];
+        if members.is_none() {
+            *members = value(Array::new());
+        }
+        if let Some(members) = members.as_array_mut() {
+            members.push(member);
+        }
+
+        let Some(dependencies) = workspace_toml["workspace"]["dependencies"].as_table_like_mut()
+        else {
+            Error::OdraNotADependency.print_and_die();
+        };
+        let odra = dependencies
+            .get("odra")
+            .cloned()
+            .unwrap_or_else(|| Error::OdraNotADependency.print_and_die());
+        for crate_name in ODRA_CRATES {
+            if !dependencies.contains_key(crate_name) {
+                dependencies.insert(crate_name, Self::sibling_dependency(&odra, crate_name));
+            }
+        }
+    }
+
+    /// Returns a dependency on another Odra crate from the same location as `odra`.
+    fn sibling_dependency(odra: &Item, crate_name: &str) -> Item {
+        let mut dependency = odra.clone();
+        if let Some(path) = dependency
+            .as_table_like_mut()
+            .and_then(|table| table.get_mut("path"))
+        {
+            if let Some(odra_path) = path.as_str() {
+                let sibling = Path::new(odra_path).with_file_name(crate_name);
+                *path = value(sibling.to_string_lossy().to_string());
+            }
+        }
+        dependency
+    }
+
+    /// Generates the first contract of a new member.
+    fn generate_contract(&self, member: &str, contract_name: String, offline: bool) {
+        if command::is_dry_run() {
+            log::info(format!(
+                "Would generate {contract_name} contract in {member}"
+            ));
+            return;
+        }
+        // Detect the project again, so it includes the new member.
+        let project = Project::detect(self.project.project_root());
+        GenerateAction::new(&project, contract_name, Some(member.to_string()), offline)
+            .generate_contract();
+    }
+
+    /// Moves workspace level parts out of the crate's Cargo.toml and returns the workspace
+    /// Cargo.toml made of them.
+    fn split_cargo_toml(member_toml: &mut Document, member: &str) -> Document {
+        let mut workspace_dependencies = Table::new();
+        for kind in CARGO_DEPENDENCIES_TABLES {
+            if let Some(dependencies) = member_toml.get_mut(kind).and_then(Item::as_table_like_mut)
+            {
+                Self::split_dependencies(dependencies, &mut workspace_dependencies);
+            }
+        }
+        if let Some(platforms) = member_toml
+            .get_mut("target")
+            .and_then(Item::as_table_like_mut)
+        {
+            for (_, platform) in platforms.iter_mut() {
+                for kind in CARGO_DEPENDENCIES_TABLES {
+                    if let Some(dependencies) =
+                        platform.get_mut(kind).and_then(Item::as_table_like_mut)
+                    {
+                        Self::split_dependencies(dependencies, &mut workspace_dependencies);
+                    }
+                }
+            }
+        }
+        if let Some(package) = member_toml
+            .get_mut("package")
+            .and_then(Item::as_table_like_mut)
+        {
+            for key in ["readme", "license-file"] {
+                if let Some(path) = package.get_mut(key) {
+                    Self::move_path_down(path);
+                }
+            }
+        }
+
+        let mut workspace = Table::new();
+        workspace.insert("members", value(Array::from_iter([member])));
+        workspace.insert("resolver", value("2"));
+        workspace.insert("dependencies", Item::Table(workspace_dependencies));
+
+        let mut workspace_toml = Document::new();
+        workspace_toml.insert("workspace", Item::Table(workspace));
+        for table in CARGO_WORKSPACE_ROOT_TABLES {
+            if let Some(item) = member_toml.remove(table) {
+                workspace_toml.insert(table, item);
+            }
+        }
+        workspace_toml
+    }
+
+    /// Makes the Odra crates of a dependencies table inherited from the workspace, adding
+    /// them to the workspace dependencies, and adjusts paths of the other ones.
+    fn split_dependencies(dependencies: &mut dyn TableLike, workspace_dependencies: &mut Table) {
+        let names: Vec<String> = dependencies
+            .iter()
+            .map(|(name, _)| name.to_string())
+            .collect();
+        for name in names {
+            let Some(dependency) = dependencies.get_mut(&name) else {
+                continue;
+            };
+            if ODRA_CRATES.contains(&name.as_str()) {
+                if !workspace_dependencies.contains_key(&name) {
+                    workspace_dependencies.insert(&name, inline(dependency.clone()));
+                }
+                *dependency = value(Self::workspace_dependency(dependency));
+                // Keys of dotted tables, like `[dependencies.odra]`, have no spacing.
+                if let Some(decor) = dependencies.key_decor_mut(&name) {
+                    *decor = Decor::default();
+                }
+            } else if let Some(path) = dependency
+                .as_table_like_mut()
+                .and_then(|table| table.get_mut("path"))
+            {
+                Self::move_path_down(path);
+            }
+        }
+    }
+
+    /// Returns a member dependency inherited from the workspace, keeping the member's
+    /// features and optionality.
+    fn workspace_dependency(dependency: &Item) -> InlineTable {
+        let mut inherited = InlineTable::new();
+        inherited.insert("workspace", true.into());
+        if let Some(table) = dependency.as_table_like() {
+            for key in ["features", "optional"] {
+                if let Some(value) = table.get(key).and_then(Item::as_value) {
+                    inherited.insert(key, value.clone());
+                }
+            }
+        }
+        inherited.fmt();
+        inherited
+    }
+
+    /// The crate goes one directory deeper, so relative paths pointing outside of it, or to
+    /// entries staying in the workspace root, like README.md, do too.
+    fn move_path_down(path: &mut Item) {
+        let Some(relative) = path.as_str().filter(|path| Path::new(path).is_relative()) else {
+            return;
+        };
+        let stays = match Path::new(relative).components().next() {
+            Some(Component::ParentDir) => true,
+            Some(Component::Normal(entry)) => {
+                WORKSPACE_ROOT_ENTRIES.contains(&entry.to_string_lossy().as_ref())
+            }
+            _ => false,
+        };
+        if stays {
+            *path = value(format!("../{relative}"));
+        }
+    }
+
+    /// Returns the project entries that belong to the crate.
+    fn entries_to_move(root: &Path) -> Vec<PathBuf> {
+        let mut entries: Vec<PathBuf> = root
+            .read_dir()
+            .map(|entries| {
+                entries
+                    .filter_map(Result::ok)
+                    .map(|entry| entry.path())
+                    .collect()
+            })
+            .unwrap_or_default();
+        entries.retain(|entry| {
+            let name = entry.file_name().unwrap_or_default().to_string_lossy();
+            !WORKSPACE_ROOT_ENTRIES.contains(&name.as_ref())
+        });
+        entries.sort();
+        entries
+    }
+
+    /// Copies the built wasm files to the member's wasm directory. They are not moved, as
+    /// builds of a workspace put them both in the root and in the member's wasm directory.
+    fn copy_wasm_files(&self, member_root: &Path) {
+        let contracts = self.project.odra_toml().contracts;
+        let wasm_files: Vec<(PathBuf, PathBuf)> = contracts
+            .iter()
+            .map(|contract| {
+                let name = contract.wasm_name();
+                (
+                    paths::wasm_path_in_wasm_dir(&name, &self.project.project_root()),
+                    paths::wasm_path_in_wasm_dir(&name, member_root),
+                )
+            })
+            .filter(|(source, _)| source.exists())
+            .collect();
+        if wasm_files.is_empty() {
+            return;
+        }
+        command::mkdir(paths::wasm_dir(member_root));
+        wasm_files
+            .into_iter()
+            .for_each(|(source, target)| command::cp(source, target));
+    }
+
+    /// Prefixes the fqns of the contracts with the member name.
+    fn update_odra_toml(&self, member: &str) {
+        let mut odra_toml = self.project.odra_toml();
+        odra_toml.contracts.iter_mut().for_each(|contract| {
+            contract.fqn = format!("{member}::{}", contract.fqn);
+        });
+        odra_toml.save();
+    }
+}
+
+/// Converts a dependency to its inline form, as used in `[workspace.dependencies]`.
+fn inline(dependency: Item) -> Item {
+    match dependency.into_value() {
+        Ok(mut value) => {
+            if let Some(table) = value.as_inline_table_mut() {
+                table.fmt();
+            }
+            Item::Value(value)
+        }
+        Err(item) => item,
+    }
+}
@@ -0,0 +1,79 @@
+//! Module with options of cargo builds run by cargo odra.
+
+use std::path::PathBuf;
+
+/// Cargo options used when building wasm files and schemas.
+#[derive(Debug, Clone, Default)]
+pub struct BuildOptions {
+    /// Cargo features to enable.
+    pub features: Vec<String>,
+    /// Disables the default features.
+    pub no_default_features: bool,
+    /// Cargo profile to build with, `release` if not set.
+    pub profile: Option<String>,
+    /// Arguments passed to cargo as they are.
+    pub passthrough_args: Vec<String>,
+}
+
+impl BuildOptions {
+    /// Returns cargo arguments selecting the profile and the features.
+    /// The profile is left out if it is selected by the passthrough arguments.
+    pub fn cargo_args(&self) -> Vec<String> {
+        let mut args = match (&self.profile, self.passthrough_profile()) {
+            (_, Some(_)) => vec![],
+            (Some(profile), None) => vec!["--profile".to_string(), profile.clone()],
+            (None, None) => vec!["--release".to_string()],
+        };
+        if !self.features.is_empty() {
+            args.push("--features".to_string());
+            args.push(self.features.join(","));
+        }
+        if self.no_default_features {
+            args.push("--no-default-features".to_string());
+        }
+        args
+    }
+
+    /// Returns the name of the directory cargo puts the profile's artifacts in.
+    pub fn profile_dir(&self) -> String {
+        let profile = self.passthrough_profile().or_else(|| self.profile.clone());
+        match profile.as_deref() {
+            None | Some("release") | Some("bench") => "release".to_string(),
+            Some("dev") | Some("test") => "debug".to_string(),
+            Some(profile) => profile.to_string(),
+        }
+    }
+
+    /// Returns the target directory set by the passthrough arguments.
+    pub fn target_dir(&self) -> Option<PathBuf> {
+        self.passthrough_value("--target-dir").map(PathBuf::from)
+    }
+
+    /// Returns the profile selected by the passthrough arguments.
+    fn passthrough_profile(&self) -> Option<String> {
+        self.passthrough_value("--profile").or_else(|| {
+            self.cargo_passthrough_args()
+                .any(|arg| arg == "--release" || arg == "-r")
+                .then(|| "release".to_string())
+        })
+    }
+
+    /// Finds the value of a `--flag value` or `--flag=value` passthrough argument.
+    fn passthrough_value(&self, flag: &str) -> Option<String> {
+        let args: Vec<&String> = self.cargo_passthrough_args().collect();
+        args.iter()
+            .enumerate()
+            .rev()
+            .find_map(|(index, arg)| match arg.strip_prefix(flag) {
+                Some("") => args.get(index + 1).map(|value| value.to_string()),
+                Some(value) => value.strip_prefix('=').map(ToString::to_string),
+                None => None,
+            })
+    }
+
+    /// Returns passthrough arguments meant for cargo, without the ones after `--`
+    /// which `cargo run` passes to the binary.
+    fn cargo_passthrough_args(&self) -> impl Iterator<Item = &String> {
+        self.passthrough_args.iter().take_while(|arg| *arg != "--")
+    }
+}
@@ -1,9 +1,10 @@
 //! Module containing functions used by Builder for managing its Cargo.toml file
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use cargo_toml::Manifest;
+use toml_edit::Document;
 
-use crate::errors::Error;
+use crate::{command, errors::Error};
 
 /// Returns Cargo.toml as Manifest struct.
 pub fn load_cargo_toml(path: &PathBuf) -> Manifest {
@@ -14,3 +15,13 @@ pub fn load_cargo_toml(path: &PathBuf) -> Manifest {
         }
     }
 }
+
+/// Returns Cargo.toml as an editable document, preserving its formatting and comments.
+pub fn load_cargo_toml_document(path: &Path) -> Document {
+    command::read_file_content(path.to_path_buf())
+        .map_err(|err| err.to_string())
+        .and_then(|content| content.parse::<Document>().map_err(|err| err.to_string()))
+        .unwrap_or_else(|err| {
+            Error::FailedToReadCargo(format!("{}: {err}", path.display())).print_and_die()
+        })
+}
@@ -1,18 +1,28 @@
 //! Module containing code that parses CLI input.
 
-use std::env;
+use std::{env, path::PathBuf};
 
 use clap::{CommandFactory, Parser, Subcommand};
 
 use crate::{
     actions::{
         build::BuildAction,
-        clean::clean_action,
+        check::CheckAction,
+        clean::CleanAction,
+        discover::DiscoverAction,
         generate::GenerateAction,
         init::InitAction,
+        remove::RemoveAction,
+        rename::RenameAction,
         schema::SchemaAction,
+        templates::TemplatesAction,
         test::TestAction,
+        upgrade::UpgradeAction,
+        verify::VerifyAction,
+        verify_bundle::VerifyBundleAction,
+        workspace::WorkspaceAction,
     },
+    build_options::BuildOptions,
     consts,
     errors::Error,
     project::Project,
@@ -40,6 +50,10 @@ pub struct Odra {
     #[clap(value_parser, long, short, global = true)]
     /// Be quiet, show only errors.
     pub quiet: bool,
+
+    #[clap(value_parser, long, global = true)]
+    /// Only print the files that would be changed and the commands that would be run.
+    pub dry_run: bool,
 }
 
 #[derive(Subcommand)]
@@ -51,14 +65,32 @@ pub enum OdraSubcommand {
     Init(InitCommand),
     /// Builds the project, including backend and producing wasm files.
     Build(BuildCommand),
+    /// Checks if Odra.toml matches the code.
+    Check(CheckCommand),
     /// Generates schema for a given contract.
     Schema(SchemaCommand),
+    /// Rebuilds a contract reproducibly and checks if it matches the given wasm file.
+    Verify(VerifyCommand),
+    /// Creates an archive with everything needed to rebuild a contract offline.
+    VerifyBundle(VerifyBundleCommand),
     /// Runs test. Without the backend parameter, the tests will be run against OdraVM.
     Test(TestCommand),
     /// Generates boilerplate code for contracts.
     Generate(GenerateCommand),
+    /// Removes a contract: its source, module declaration, Odra.toml entry and wasm files.
+    Remove(RemoveCommand),
+    /// Renames a contract: its struct, module, declarations and Odra.toml entry.
+    Rename(RenameCommand),
+    /// Finds contracts in the sources and prints their Odra.toml entries.
+    Discover(DiscoverCommand),
     /// Cleans all temporary data generated by cargo odra.
     Clean(CleanCommand),
+    /// Manages the cache of Odra templates.
+    Templates(TemplatesCommand),
+    /// Manages the workspace of the project.
+    Workspace(WorkspaceCommand),
+    /// Upgrades Odra used by the project.
+    Upgrade(UpgradeCommand),
     /// Generates completions for given shell
     Completions {
         /// The shell to generate the completions for
@@ -71,13 +103,15 @@ pub enum OdraSubcommand {
 /// `cargo odra init`
 pub struct InitCommand {
     /// Name which will be used as a name for the crate.
+    #[clap(value_parser, long, short, required_unless_present = "list_templates")]
+    pub name: Option<String>,
+    /// URI of the repository containing the templates, by default the Odra repository.
+    /// For git sources, it is also the repository of the Odra dependency.
     #[clap(value_parser, long, short)]
-    pub name: String,
-    /// URI of the repository containing the template.
-    #[clap(value_parser, long, short, default_value = consts::ODRA_TEMPLATE_GH_REPO)]
-    pub repo_uri: String,
+    pub repo_uri: Option<String>,
     /// Odra source to use. By default, it uses latest release of Odra.
-    /// It can be a version, a branch, commit hash or a location on the filesystem.
+    /// It can be a version, a location on the filesystem, `branch:<name>`, `tag:<name>`
+    /// or `rev:<commit hash>`. Any other value is a branch name.
     #[clap(value_parser, long, short)]
     pub source: Option<String>,
     /// Template to use. Default is "full" - which contains a sample contract and a test.
@@ -86,6 +120,25 @@ pub struct InitCommand {
     /// "workspace" - which sets up a workspace with a sub crate.
     #[clap(value_parser, long, short, default_value = consts::ODRA_TEMPLATE_DEFAULT_TEMPLATE)]
     pub template: String,
+    /// Lists templates available in the selected Odra source, instead of creating a project.
+    #[clap(value_parser, long, default_value = "false")]
+    pub list_templates: bool,
+    /// Uses only the cached Odra sources, see `cargo odra templates fetch`.
+    #[clap(value_parser, long, default_value = "false")]
+    pub offline: bool,
+    /// Template variable in the `key=value` form, passed to cargo-generate.
+    /// Can be used multiple times.
+    #[clap(value_parser, long, short)]
+    pub define: Vec<String>,
+    /// TOML file with template variables, passed to cargo-generate.
+    #[clap(value_parser, long)]
+    pub values_file: Option<PathBuf>,
+    /// Version control system to initialize the project with.
+    #[clap(long, value_parser = ["none", "git"], default_value = "git")]
+    pub vcs: String,
+    /// Does not prompt for template variables, they have to be defined or have defaults.
+    #[clap(value_parser, long, default_value = "false")]
+    pub silent: bool,
 }
 
 #[derive(clap::Args)]
@@ -94,6 +147,63 @@ pub struct BuildCommand {
     /// Contracts names separated by a space that matches the names in Odra.toml.
     #[clap(value_parser, long, short)]
     pub contracts_names: Option<String>,
+    /// Builds wasm files reproducibly: pins the toolchain from the toolchain file,
+    /// requires an up-to-date Cargo.lock and records the versions of the tools used.
+    #[clap(value_parser, long, default_value = "false")]
+    pub reproducible: bool,
+    #[clap(flatten)]
+    pub cargo: CargoBuildArgs,
+}
+
+#[derive(clap::Args, Debug)]
+/// Cargo options of `cargo odra build` and `cargo odra schema`.
+pub struct CargoBuildArgs {
+    /// Cargo features to enable, separated by commas.
+    #[clap(value_parser, long, short = 'F', value_delimiter = ',')]
+    pub features: Vec<String>,
+    /// Disables the default features.
+    #[clap(value_parser, long, default_value = "false")]
+    pub no_default_features: bool,
+    /// Cargo profile to build with. Defaults to release.
+    #[clap(value_parser, long)]
+    pub profile: Option<String>,
+    /// A list of arguments is passed to the cargo command.
+    #[clap(raw = true)]
+    pub args: Vec<String>,
+}
+
+impl From<CargoBuildArgs> for BuildOptions {
+    fn from(args: CargoBuildArgs) -> Self {
+        BuildOptions {
+            features: args.features,
+            no_default_features: args.no_default_features,
+            profile: args.profile,
+            passthrough_args: args.args,
+        }
+    }
+}
+
+#[derive(clap::Args)]
+/// `cargo odra check`
+pub struct CheckCommand {}
+
+#[derive(clap::Args)]
+/// `cargo odra verify`
+pub struct VerifyCommand {
+    /// Wasm file to verify.
+    #[clap(value_parser)]
+    pub wasm: PathBuf,
+    /// Name of the contract in Odra.toml. By default, it is taken from the wasm file name.
+    #[clap(value_parser, long, short)]
+    pub contract: Option<String>,
+}
+
+#[derive(clap::Args)]
+/// `cargo odra verify-bundle`
+pub struct VerifyBundleCommand {
+    /// Name of the contract in Odra.toml.
+    #[clap(value_parser, long, short)]
+    pub contract: String,
 }
 
 #[derive(clap::Args)]
@@ -102,6 +212,8 @@ pub struct SchemaCommand {
     /// Contracts names separated by a space that matches the names in Odra.toml.
     #[clap(value_parser, long, short)]
     pub contracts_names: Option<String>,
+    #[clap(flatten)]
+    pub cargo: CargoBuildArgs,
 }
 
 #[derive(clap::Args, Debug)]
@@ -119,8 +231,40 @@ pub struct TestCommand {
 }
 
 #[derive(clap::Args, Debug)]
+#[clap(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
 /// `cargo odra generate`
 pub struct GenerateCommand {
+    #[clap(subcommand)]
+    pub target: Option<GenerateSubcommand>,
+    /// Name of the contract to be created. Same as `generate contract`.
+    #[clap(value_parser, long, short, required = true)]
+    pub contract_name: Option<String>,
+    /// Name of the module in which the contract will be created.
+    #[clap(value_parser, long, short)]
+    pub module: Option<String>,
+    /// Use only cached templates, without accessing the network.
+    #[clap(value_parser, long, default_value = "false", global = true)]
+    pub offline: bool,
+}
+
+#[derive(Subcommand, Debug)]
+/// Subcommands of `cargo odra generate`.
+pub enum GenerateSubcommand {
+    /// Generates a contract and adds it to Odra.toml.
+    Contract(GenerateContractCommand),
+    /// Generates a module, which is not deployable on its own.
+    Module(GenerateItemCommand),
+    /// Generates an event and registers it in a contract.
+    Event(GenerateEventCommand),
+    /// Generates an error enum.
+    Error(GenerateItemCommand),
+    /// Generates tests for a contract from Odra.toml.
+    Test(GenerateTestCommand),
+}
+
+#[derive(clap::Args, Debug)]
+/// `cargo odra generate contract`
+pub struct GenerateContractCommand {
     /// Name of the contract to be created.
     #[clap(value_parser, long, short)]
     pub contract_name: String,
@@ -129,9 +273,166 @@ pub struct GenerateCommand {
     pub module: Option<String>,
 }
 
+#[derive(clap::Args, Debug)]
+/// `cargo odra generate module` and `cargo odra generate error`
+pub struct GenerateItemCommand {
+    /// Name of the item to be created.
+    #[clap(value_parser, long, short)]
+    pub name: String,
+    /// Name of the module in which the item will be created.
+    #[clap(value_parser, long, short)]
+    pub module: Option<String>,
+}
+
+#[derive(clap::Args, Debug)]
+/// `cargo odra generate event`
+pub struct GenerateEventCommand {
+    /// Name of the event to be created.
+    #[clap(value_parser, long, short)]
+    pub name: String,
+    /// Name of the contract emitting the event, as in Odra.toml.
+    #[clap(value_parser, long, short)]
+    pub contract_name: String,
+}
+
+#[derive(clap::Args, Debug)]
+/// `cargo odra generate test`
+pub struct GenerateTestCommand {
+    /// Name of the tested contract, as in Odra.toml.
+    #[clap(value_parser, long, short)]
+    pub contract_name: String,
+}
+
+#[derive(clap::Args, Debug)]
+/// `cargo odra remove`
+pub struct RemoveCommand {
+    /// Name of the contract to be removed.
+    #[clap(value_parser, long, short)]
+    pub contract_name: String,
+    /// Name of the module containing the contract.
+    #[clap(value_parser, long, short)]
+    pub module: Option<String>,
+    /// Keep the contract's source file and its declaration in src/lib.rs.
+    #[clap(value_parser, long, default_value = "false")]
+    pub keep_source: bool,
+}
+
+#[derive(clap::Args, Debug)]
+/// `cargo odra rename`
+pub struct RenameCommand {
+    /// Name of the contract to be renamed.
+    #[clap(value_parser, long, short)]
+    pub contract_name: String,
+    /// New name of the contract.
+    #[clap(value_parser, long)]
+    pub to: String,
+    /// Name of the module containing the contract.
+    #[clap(value_parser, long, short)]
+    pub module: Option<String>,
+}
+
+#[derive(clap::Args, Debug)]
+/// `cargo odra discover`
+pub struct DiscoverCommand {
+    /// Add the contracts missing in Odra.toml to it.
+    #[clap(value_parser, long, default_value = "false")]
+    pub write: bool,
+}
+
+#[derive(clap::Args, Debug)]
+/// `cargo odra templates`
+pub struct TemplatesCommand {
+    #[clap(subcommand)]
+    pub subcommand: TemplatesSubcommand,
+}
+
+#[derive(Subcommand, Debug)]
+/// Subcommands of `cargo odra templates`.
+pub enum TemplatesSubcommand {
+    /// Downloads templates of the given Odra version, so projects can be created offline.
+    Fetch(TemplatesFetchCommand),
+}
+
+#[derive(clap::Args, Debug)]
+/// `cargo odra templates fetch`
+pub struct TemplatesFetchCommand {
+    /// Version of Odra. Defaults to the latest release.
+    #[clap(value_parser)]
+    pub version: Option<String>,
+}
+
+#[derive(clap::Args, Debug)]
+/// `cargo odra upgrade`
+pub struct UpgradeCommand {
+    /// Odra source to upgrade to: a version, a location on the filesystem, `branch:<name>`,
+    /// `tag:<name>` or `rev:<commit hash>`. Defaults to the latest release.
+    #[clap(value_parser, long)]
+    pub to: Option<String>,
+    /// Only reports whether a newer Odra release exists.
+    #[clap(value_parser, long, default_value = "false", conflicts_with = "to")]
+    pub check: bool,
+    /// Uses only the cached Odra sources.
+    #[clap(value_parser, long, default_value = "false")]
+    pub offline: bool,
+}
+
+#[derive(clap::Args, Debug)]
+/// `cargo odra workspace`
+pub struct WorkspaceCommand {
+    #[clap(subcommand)]
+    pub subcommand: WorkspaceSubcommand,
+}
+
+#[derive(Subcommand, Debug)]
+/// Subcommands of `cargo odra workspace`.
+pub enum WorkspaceSubcommand {
+    /// Converts a single crate project into a workspace with the crate as its member.
+    ///
+    /// Built wasm files are copied to the member's wasm directory and kept in the root one,
+    /// like builds of a workspace leave them.
+    Convert,
+    /// Adds a new Odra crate to the workspace.
+    AddMember(WorkspaceAddMemberCommand),
+}
+
+#[derive(clap::Args, Debug)]
+/// `cargo odra workspace add-member`
+pub struct WorkspaceAddMemberCommand {
+    /// Name of the new crate.
+    #[clap(value_parser)]
+    pub name: String,
+    /// Name of the first contract to generate in the crate.
+    #[clap(value_parser, long, short)]
+    pub contract_name: Option<String>,
+    /// Uses only the cached templates to generate the contract.
+    #[clap(value_parser, long, default_value = "false")]
+    pub offline: bool,
+}
+
 #[derive(clap::Args, Debug)]
 /// `cargo odra clean`
-pub struct CleanCommand {}
+pub struct CleanCommand {
+    /// Removes only wasm files, keeping the cargo artifacts.
+    #[clap(value_parser, long, default_value = "false")]
+    pub wasm_only: bool,
+    /// Removes only the wasm files of the given contract. Can be used multiple times.
+    #[clap(value_parser, long, short)]
+    pub contract: Vec<String>,
+    /// Removes also the schema files.
+    #[clap(value_parser, long, default_value = "false")]
+    pub schemas: bool,
+    /// Cleans only the given member of the workspace.
+    #[clap(value_parser, long, short)]
+    pub member: Option<String>,
+    /// Runs `cargo clean` for the whole project, not only for the Odra crates.
+    #[clap(
+        value_parser,
+        long,
+        default_value = "false",
+        conflicts_with_all = ["wasm_only", "contract", "member"]
+    )]
+    pub all: bool,
+}
 
 #[derive(clap::Args, Debug)]
 /// `cargo odra update`
@@ -149,7 +450,25 @@ pub fn make_action() {
     match args.subcommand {
         OdraSubcommand::Build(build) => {
             let project = Project::detect(current_dir);
-            BuildAction::new(&project, build.contracts_names).build();
+            BuildAction::new(
+                &project,
+                build.contracts_names,
+                build.reproducible,
+                build.cargo.into(),
+            )
+            .build();
+        }
+        OdraSubcommand::Check(_) => {
+            let project = Project::detect(current_dir);
+            CheckAction::new(&project).check();
+        }
+        OdraSubcommand::Verify(verify) => {
+            let project = Project::detect(current_dir);
+            VerifyAction::new(&project, verify.wasm, verify.contract).verify();
+        }
+        OdraSubcommand::VerifyBundle(verify_bundle) => {
+            let project = Project::detect(current_dir);
+            VerifyBundleAction::new(&project, verify_bundle.contract).create_bundle();
         }
         OdraSubcommand::Test(test) => {
             let project = Project::detect(current_dir);
@@ -157,8 +476,38 @@ pub fn make_action() {
         }
         OdraSubcommand::Generate(generate) => {
             let project = Project::detect(current_dir);
-            GenerateAction::new(&project, generate.contract_name, generate.module)
-                .generate_contract();
+            let offline = generate.offline;
+            match generate.target {
+                None => GenerateAction::new(
+                    &project,
+                    generate.contract_name.unwrap_or_default(),
+                    generate.module,
+                    offline,
+                )
+                .generate_contract(),
+                Some(GenerateSubcommand::Contract(contract)) => {
+                    GenerateAction::new(&project, contract.contract_name, contract.module, offline)
+                        .generate_contract()
+                }
+                Some(GenerateSubcommand::Module(module)) => {
+                    GenerateAction::new(&project, module.name, module.module, offline)
+                        .generate_module()
+                }
+                Some(GenerateSubcommand::Event(event)) => {
+                    GenerateAction::new(&project, event.name, None, offline)
+                        .generate_event(&event.contract_name)
+                }
+                Some(GenerateSubcommand::Error(error)) => {
+                    GenerateAction::new(&project, error.name, error.module, offline)
+                        .generate_error()
+                }
+                Some(GenerateSubcommand::Test(test)) => {
+                    GenerateAction::new(&project, test.contract_name, None, offline).generate_test()
+                }
+            }
+        }
+        OdraSubcommand::New(init) | OdraSubcommand::Init(init) if init.list_templates => {
+            InitAction::list_templates(init.source, init.repo_uri, init.offline);
         }
         OdraSubcommand::New(init) => {
             InitAction::generate_project(init, current_dir, false);
@@ -166,16 +515,64 @@ pub fn make_action() {
         OdraSubcommand::Init(init) => {
             InitAction::generate_project(init, current_dir, true);
         }
-        OdraSubcommand::Clean(_) => {
+        OdraSubcommand::Remove(remove) => {
+            let project = Project::detect(current_dir);
+            RemoveAction::new(
+                &project,
+                remove.contract_name,
+                remove.module,
+                remove.keep_source,
+            )
+            .remove();
+        }
+        OdraSubcommand::Rename(rename) => {
+            let project = Project::detect(current_dir);
+            RenameAction::new(&project, rename.contract_name, rename.to, rename.module).rename();
+        }
+        OdraSubcommand::Discover(discover) => {
+            DiscoverAction::new(current_dir, discover.write).discover();
+        }
+        OdraSubcommand::Clean(clean) => {
+            let project = Project::detect(current_dir);
+            CleanAction::new(
+                &project,
+                clean.contract,
+                clean.member,
+                clean.wasm_only,
+                clean.schemas,
+                clean.all,
+            )
+            .clean();
+        }
+        OdraSubcommand::Templates(templates) => match templates.subcommand {
+            TemplatesSubcommand::Fetch(fetch) => TemplatesAction::fetch(fetch.version),
+        },
+        OdraSubcommand::Upgrade(upgrade) => {
+            let project = Project::detect(current_dir);
+            let action = UpgradeAction::new(&project, upgrade.to, upgrade.offline);
+            match upgrade.check {
+                true => action.check(),
+                false => action.upgrade(),
+            }
+        }
+        OdraSubcommand::Workspace(workspace) => {
             let project = Project::detect(current_dir);
-            clean_action(&project);
+            match workspace.subcommand {
+                WorkspaceSubcommand::Convert => WorkspaceAction::new(&project).convert(),
+                WorkspaceSubcommand::AddMember(add_member) => WorkspaceAction::new(&project)
+                    .add_member(
+                        &add_member.name,
+                        add_member.contract_name,
+                        add_member.offline,
+                    ),
+            }
         }
         OdraSubcommand::Completions { shell } => {
             shell.generate(&mut Cargo::command(), &mut std::io::stdout());
         }
         OdraSubcommand::Schema(schema) => {
             let project = Project::detect(current_dir);
-            SchemaAction::new(&project, schema.contracts_names).build();
+            SchemaAction::new(&project, schema.contracts_names, schema.cargo.into()).build();
         }
     }
 }
@@ -1,11 +1,12 @@
 //! Module containing code that runs external commands.
 
 use std::{
+    collections::VecDeque,
     env,
-    fs::{self, File, OpenOptions},
-    io::{self, Write},
-    path::PathBuf,
-    process::{Command, ExitStatus},
+    io::{self, BufRead, BufReader, IsTerminal},
+    path::{Path, PathBuf},
+    process::{Command, ExitStatus, Output, Stdio},
+    time::Instant,
 };
 
 use clap::Parser;
@@ -13,10 +14,14 @@ use Error::InvalidInternalCommand;
 
 use crate::{
     cli::Cargo,
-    consts::{ODRA_BACKEND_ENV_KEY, ODRA_MODULE_ENV_KEY},
-    errors::Error,
+    command_log,
+    consts::{COMMAND_STDERR_TAIL_LINES, ODRA_BACKEND_ENV_KEY, ODRA_MODULE_ENV_KEY},
+    errors::{CommandError, CommandStderr, Error},
+    fs,
+    http,
     log,
     paths,
+    project::GitReference,
 };
 
 /// Returns output of a command as a String.
@@ -26,72 +31,185 @@ pub fn command_output(command: &str) -> String {
         .first()
         .unwrap_or_else(|| InvalidInternalCommand(command.to_string()).print_and_die());
     let args: Vec<&str> = split_command.drain(1..).collect();
-    let output = Command::new(program).args(args).output().unwrap();
+    let output = output(Command::new(program).args(args)).unwrap();
     std::str::from_utf8(output.stdout.as_slice())
         .unwrap()
         .to_string()
 }
 
-/// Quits if status of a command is not successful.
-pub fn parse_command_result(status: ExitStatus, error: Error) {
-    if !status.success() {
-        error.print_and_die();
-    }
-}
-
 /// Copies file
 pub fn cp(source: PathBuf, target: PathBuf) {
-    let status = Command::new("cp")
-        .args([&source, &target])
-        .status()
-        .unwrap();
-
-    parse_command_result(
-        status,
-        Error::CommandFailed(format!(
-            "Couldn't copy {} to {}",
-            source.display(),
-            target.display()
-        )),
-    );
+    fs::copy(&source, &target).unwrap_or_else(|err| err.print_and_die());
 }
 
 /// Remove a directory.
 pub fn rm_dir(path: PathBuf) {
-    log::info(format!("Removing {}...", path.display()));
-    let result = rm_rf::ensure_removed(path.clone());
-    if result.is_err() {
-        Error::RemoveDirNotPossible(path).print_and_die();
-    };
+    if !is_dry_run() {
+        log::info(format!("Removing {}...", path.display()));
+    }
+    fs::remove_dir(&path).unwrap_or_else(|err| err.print_and_die());
+}
+
+/// Removes a file.
+pub fn rm_file(path: PathBuf) {
+    if !is_dry_run() {
+        log::info(format!("Removing {}...", path.display()));
+    }
+    fs::remove_file(&path).unwrap_or_else(|err| err.print_and_die());
 }
 
 /// Creates a directory.
 pub fn mkdir(path: PathBuf) {
-    fs::create_dir_all(path).unwrap();
+    fs::create_dir(&path).unwrap_or_else(|err| err.print_and_die());
 }
 
 /// Runs wasm-strip and wasm-opt on a given contract's wasm file.
 pub fn process_wasm(contract_name: &str, project_root: PathBuf) {
-    let command = Command::new("wasm-opt")
+    let wasm_path = paths::wasm_path_in_wasm_dir(contract_name, &project_root);
+    if is_dry_run() {
+        log::info(format!(
+            "Would run wasm-opt and wasm-strip on {}",
+            wasm_path.display()
+        ));
+        return;
+    }
+
+    run(Command::new("wasm-opt")
         .current_dir(project_root.clone())
         .arg("--signext-lowering")
-        .arg(paths::wasm_path_in_wasm_dir(contract_name, &project_root))
+        .arg(&wasm_path)
         .arg("-o")
-        .arg(paths::wasm_path_in_wasm_dir(contract_name, &project_root))
-        .status();
+        .arg(&wasm_path))
+    .unwrap_or_else(|err| Error::WasmoptDidNotFinish(err).print_and_die());
 
-    if command.is_err() || !command.unwrap().success() {
-        Error::WasmoptDidNotFinish.print_and_die();
+    run(Command::new("wasm-strip")
+        .current_dir(project_root)
+        .arg(&wasm_path))
+    .unwrap_or_else(|err| Error::WasmstripDidNotFinish(err).print_and_die());
+}
+
+/// Makes a shallow clone of a git repository, at the given branch or tag if set.
+pub fn git_clone(
+    repository: &str,
+    reference: Option<&GitReference>,
+    target: &Path,
+) -> Result<(), Error> {
+    log::info(format!("Fetching {repository}..."));
+    let failed = |err: CommandError| {
+        let source = match reference {
+            Some(reference) => format!("{repository} at {reference}"),
+            None => repository.to_string(),
+        };
+        Error::FailedToFetchTemplate(source, err.into())
+    };
+
+    let mut clone = git();
+    clone.args(["clone", "--quiet"]);
+    match reference {
+        // A revision can not be cloned directly, it is checked out from the full history.
+        Some(GitReference::Rev(_)) => {}
+        Some(reference) => {
+            clone.args(["--depth", "1", "--branch", reference.name()]);
+        }
+        None => {
+            clone.args(["--depth", "1"]);
+        }
     }
+    run(clone.arg(repository).arg(target)).map_err(failed)?;
 
-    let command = Command::new("wasm-strip")
-        .current_dir(project_root.clone())
-        .arg(paths::wasm_path_in_wasm_dir(contract_name, &project_root))
-        .status();
+    if let Some(GitReference::Rev(rev)) = reference {
+        let mut checkout = git();
+        checkout
+            .arg("-C")
+            .arg(target)
+            .args(["checkout", "--quiet", rev]);
+        run(&mut checkout).map_err(failed)?;
+    }
+    Ok(())
+}
+
+/// Returns git command configured with the proxy, if set.
+fn git() -> Command {
+    let mut command = Command::new("git");
+    if let Some(proxy) = http::proxy() {
+        command.args(["-c", &format!("http.proxy={proxy}")]);
+    }
+    command
+}
+
+/// Runs a command and returns its output. The command is logged.
+pub fn output(command: &mut Command) -> io::Result<Output> {
+    let started = Instant::now();
+    let output = command.output();
+    record(
+        command,
+        started,
+        output.as_ref().map(|output| output.status),
+    );
+    output
+}
+
+/// Runs a command, failing if it could not be started or did not succeed. The command
+/// is logged.
+fn run(command: &mut Command) -> Result<(), CommandError> {
+    let started = Instant::now();
+    let status = command.status();
+    record(command, started, status.as_ref().copied());
+    let status = status.map_err(CommandError::NotStarted)?;
+    match status.success() {
+        true => Ok(()),
+        false => Err(CommandError::Exited(
+            status,
+            command_log::reproduction(command),
+            None,
+        )),
+    }
+}
+
+/// Runs a command, passing its stderr through and keeping the last lines of it
+/// for the error report.
+fn run_capturing_stderr(command: &mut Command) -> Result<(), CommandError> {
+    let started = Instant::now();
+    let mut child = match command.stderr(Stdio::piped()).spawn() {
+        Ok(child) => child,
+        Err(err) => {
+            record(command, started, Err(&err));
+            return Err(CommandError::NotStarted(err));
+        }
+    };
 
-    if command.is_err() || !command.unwrap().success() {
-        Error::WasmstripDidNotFinish.print_and_die();
+    let mut tail = VecDeque::with_capacity(COMMAND_STDERR_TAIL_LINES);
+    if let Some(stderr) = child.stderr.take() {
+        for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+            eprintln!("{line}");
+            if tail.len() == COMMAND_STDERR_TAIL_LINES {
+                tail.pop_front();
+            }
+            tail.push_back(line);
+        }
     }
+
+    let status = child.wait();
+    record(command, started, status.as_ref().copied());
+    let status = status.map_err(CommandError::NotStarted)?;
+    if status.success() {
+        return Ok(());
+    }
+    let stderr = Vec::from(tail).join("\n");
+    let stderr = (!stderr.trim().is_empty()).then_some(CommandStderr(stderr));
+    Err(CommandError::Exited(
+        status,
+        command_log::reproduction(command),
+        stderr,
+    ))
+}
+
+fn record(command: &Command, started: Instant, status: Result<ExitStatus, &io::Error>) {
+    let status = match status {
+        Ok(status) => status.to_string(),
+        Err(err) => format!("failed to start: {err}"),
+    };
+    command_log::record(command, started.elapsed(), &status);
 }
 
 /// Runs cargo with given args.
@@ -106,40 +224,90 @@ fn cargo(current_dir: PathBuf, command: &str, tail_args: Vec<&str>) {
         args.push(arg);
     }
 
-    let command = Command::new("cargo")
-        .current_dir(current_dir)
-        .args(args.as_slice())
-        .status()
-        .unwrap();
+    if is_dry_run() {
+        log::info(format!("Would run cargo {}", args.join(" ")));
+        return;
+    }
 
-    parse_command_result(
-        command,
-        Error::CommandFailed(format!("Couldn't run cargo with args {args:?}")),
-    );
+    let mut command = Command::new("cargo");
+    command.current_dir(current_dir).args(args.as_slice());
+    // Cargo does not color piped output, unless asked to.
+    if io::stderr().is_terminal() && env::var_os("CARGO_TERM_COLOR").is_none() {
+        command.env("CARGO_TERM_COLOR", "always");
+    }
+
+    run_capturing_stderr(&mut command).unwrap_or_else(|err| {
+        Error::CommandFailed(format!("cargo {}", args.join(" ")), err).print_and_die()
+    });
+}
+
+/// Runs cargo with given args and returns its stdout. Its stderr is kept for the
+/// error report.
+pub fn cargo_output(current_dir: &Path, args: &[&str]) -> String {
+    let mut command = Command::new("cargo");
+    command.current_dir(current_dir).args(args);
+    let result = output(&mut command)
+        .map_err(CommandError::NotStarted)
+        .and_then(|output| match output.status.success() {
+            true => Ok(output.stdout),
+            false => {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                let lines: Vec<&str> = stderr.lines().collect();
+                let tail =
+                    lines[lines.len().saturating_sub(COMMAND_STDERR_TAIL_LINES)..].join("\n");
+                Err(CommandError::Exited(
+                    output.status,
+                    command_log::reproduction(&command),
+                    (!tail.trim().is_empty()).then_some(CommandStderr(tail)),
+                ))
+            }
+        });
+    match result {
+        Ok(stdout) => String::from_utf8_lossy(&stdout).to_string(),
+        Err(err) => Error::CommandFailed(format!("cargo {}", args.join(" ")), err).print_and_die(),
+    }
 }
 
 /// Build wasm files.
-pub fn cargo_build_wasm_files(current_dir: PathBuf, contract_name: &str, module_name: &str) {
+pub fn cargo_build_wasm_files(
+    current_dir: PathBuf,
+    contract_name: &str,
+    module_name: &str,
+    extra_args: &[String],
+) {
     env::set_var(ODRA_MODULE_ENV_KEY, contract_name);
-    let build_contract = format!("{}_build_contract", module_name);
+    let args = wasm_build_args(module_name, extra_args);
     cargo(
         current_dir,
         "build",
-        vec![
-            "--target",
-            "wasm32-unknown-unknown",
-            "--bin",
-            &build_contract,
-            "--release",
-        ],
+        args.iter().map(String::as_str).collect(),
     );
 }
 
+/// Returns arguments passed to `cargo build` when building a contract's wasm file.
+pub fn wasm_build_args(module_name: &str, extra_args: &[String]) -> Vec<String> {
+    let mut args: Vec<String> = vec![
+        "--target".to_string(),
+        "wasm32-unknown-unknown".to_string(),
+        "--bin".to_string(),
+        format!("{}_build_contract", module_name),
+    ];
+    args.extend_from_slice(extra_args);
+    args
+}
+
 /// Build schema files.
-pub fn cargo_generate_schema_files(current_dir: PathBuf, contract_name: &str, module_name: &str) {
+pub fn cargo_generate_schema_files(
+    current_dir: PathBuf,
+    contract_name: &str,
+    module_name: &str,
+    extra_args: &[String],
+) {
     env::set_var(ODRA_MODULE_ENV_KEY, contract_name);
     let gen_schema = format!("{}_build_schema", module_name);
-    cargo(current_dir, "run", vec!["--bin", &gen_schema, "--release"]);
+    let mut args = vec!["--bin", &gen_schema];
+    args.extend(extra_args.iter().map(String::as_str));
+    cargo(current_dir, "run", args);
 }
 
 /// Runs cargo test.
@@ -159,32 +327,30 @@ pub fn cargo_test_backend(project_root: PathBuf, backend_name: &str, mut args: V
     cargo(project_root, "test", tail_args)
 }
 
-/// Runs cargo clean.
-pub fn cargo_clean(current_dir: PathBuf) {
+/// Runs cargo clean, for the given packages only if any are given.
+pub fn cargo_clean(current_dir: PathBuf, packages: &[String]) {
     log::info("Running cargo clean...");
-    cargo(current_dir, "clean", vec![]);
+    let args = packages
+        .iter()
+        .flat_map(|package| ["--package", package.as_str()])
+        .collect();
+    cargo(current_dir, "clean", args);
 }
 
 /// Writes a content to a file at the given path.
 pub fn write_to_file(path: PathBuf, content: &str) {
-    let mut file = File::create(path).unwrap();
-    file.write_all(content.as_bytes()).unwrap();
+    fs::write(&path, content).unwrap_or_else(|err| err.print_and_die());
 }
 
 /// Appends a content to a file at the given path.
 pub fn append_file(path: PathBuf, content: &str) {
-    let mut file = OpenOptions::new()
-        .write(true)
-        .append(true)
-        .open(path)
-        .unwrap();
-
-    file.write_all(content.as_bytes()).unwrap();
+    fs::append(&path, content).unwrap_or_else(|err| err.print_and_die());
 }
 
 /// Replaces strings in a file.
 pub fn replace_in_file(path: PathBuf, from: &str, to: &str) {
-    let content = read_file_content(path.clone()).unwrap();
+    let content = read_file_content(path.clone())
+        .unwrap_or_else(|err| Error::FailedToReadFile(path.clone(), err).print_and_die());
     let new_content = content.replace(from, to);
     write_to_file(path, new_content.as_str());
 }
@@ -194,17 +360,17 @@ pub fn rename_file(path: PathBuf, new_name: &str) {
     let mut new_path = path.clone();
     new_path.pop();
     new_path.push(new_name);
-    fs::rename(path, new_path).unwrap();
+    fs::rename(&path, &new_path).unwrap_or_else(|err| err.print_and_die());
 }
 
 /// Loads a file to a string.
 pub fn read_file_content(path: PathBuf) -> io::Result<String> {
-    fs::read_to_string(path)
+    std::fs::read_to_string(path)
 }
 
 // TODO: Is there a better way? A global static to hold that?
 /// Extracts verbosity, by parsing bin arguments.
-fn verbosity_arg<'a>() -> Option<&'a str> {
+pub fn verbosity_arg<'a>() -> Option<&'a str> {
     let Cargo::Odra(args) = Cargo::parse();
     if args.verbose {
         Some("--verbose")
@@ -214,3 +380,9 @@ fn verbosity_arg<'a>() -> Option<&'a str> {
         None
     }
 }
+
+/// Checks if the command should only print the changes it would make, by parsing bin arguments.
+pub fn is_dry_run() -> bool {
+    let Cargo::Odra(args) = Cargo::parse();
+    args.dry_run
+}
@@ -0,0 +1,126 @@
+//! Module recording external commands run by cargo-odra, so failed builds can be reproduced.
+
+use std::{
+    collections::BTreeMap,
+    env,
+    fs::{self, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+    process::Command,
+    sync::OnceLock,
+    time::Duration,
+};
+
+use chrono::Local;
+
+use crate::{
+    consts::{COMMAND_LOGS_DIR, COMMAND_LOG_ENV_KEYS},
+    log,
+    paths,
+    project::Project,
+};
+
+static LOG_FILE: OnceLock<Option<PathBuf>> = OnceLock::new();
+
+/// Returns the file the commands of this run are logged to, in the target directory
+/// of the project. Outside of a project, commands are not logged.
+pub fn log_file() -> Option<&'static Path> {
+    LOG_FILE
+        .get_or_init(|| {
+            let project_root = Project::find_project_root(env::current_dir().ok()?)?;
+            let name = format!("{}.log", Local::now().format("%Y-%m-%dT%H-%M-%S%.3f"));
+            Some(
+                paths::build_target_dir(&project_root)
+                    .join(COMMAND_LOGS_DIR)
+                    .join(name),
+            )
+        })
+        .as_deref()
+}
+
+/// Appends a finished command, with its cwd, env, duration and status, to the log file.
+///
+/// Failing to write the log does not fail the command, it is only reported in verbose mode.
+pub fn record(command: &Command, duration: Duration, status: &str) {
+    let Some(path) = log_file() else {
+        return;
+    };
+    let env = env_of(command)
+        .iter()
+        .map(|(key, value)| format!("{key}={}", quote(value)))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let entry = format!(
+        "[{}] {status} after {duration:.2?}\ncwd: {}\nenv: {env}\ncommand: {}\n\n",
+        Local::now().format("%Y-%m-%d %H:%M:%S"),
+        cwd_of(command).display(),
+        command_line(command),
+    );
+
+    let written = path
+        .parent()
+        .map_or(Ok(()), fs::create_dir_all)
+        .and_then(|_| OpenOptions::new().create(true).append(true).open(path))
+        .and_then(|mut file| file.write_all(entry.as_bytes()));
+    if let Err(err) = written {
+        log::verbose(format!(
+            "Could not log the command to {}: {err}",
+            path.display()
+        ));
+    }
+}
+
+/// Returns a shell command line running the command again, in its directory and env.
+pub fn reproduction(command: &Command) -> String {
+    let env = env_of(command)
+        .into_iter()
+        .map(|(key, value)| format!("{key}={}", quote(&value)));
+    let line = env
+        .chain([command_line(command)])
+        .collect::<Vec<_>>()
+        .join(" ");
+    format!("cd {} && {line}", quote(&cwd_of(command).to_string_lossy()))
+}
+
+/// Recorded env of the command: the inherited variables that affect builds, and the ones
+/// set for the command.
+fn env_of(command: &Command) -> BTreeMap<String, String> {
+    let mut env: BTreeMap<String, String> = COMMAND_LOG_ENV_KEYS
+        .iter()
+        .filter_map(|key| env::var(key).ok().map(|value| (key.to_string(), value)))
+        .collect();
+    command.get_envs().for_each(|(key, value)| {
+        let key = key.to_string_lossy().to_string();
+        match value {
+            Some(value) => env.insert(key, value.to_string_lossy().to_string()),
+            None => env.remove(&key),
+        };
+    });
+    env
+}
+
+fn cwd_of(command: &Command) -> PathBuf {
+    let current_dir = env::current_dir().unwrap_or_default();
+    match command.get_current_dir() {
+        Some(dir) => current_dir.join(dir),
+        None => current_dir,
+    }
+}
+
+fn command_line(command: &Command) -> String {
+    [command.get_program()]
+        .into_iter()
+        .chain(command.get_args())
+        .map(|arg| quote(&arg.to_string_lossy()))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Quotes a word for a POSIX shell, if needed.
+fn quote(word: &str) -> String {
+    let safe = |c: char| c.is_ascii_alphanumeric() || "_-./=:,+@%".contains(c);
+    match !word.is_empty() && word.chars().all(safe) {
+        true => word.to_string(),
+        false => format!("'{}'", word.replace('\'', r"'\''")),
+    }
+}
@@ -15,13 +15,255 @@ pub const ODRA_TEMPLATE_GH_REPO: &str = "https://github.com/odradev/odra.git";
 /// Template raw repository path.
 pub const ODRA_TEMPLATE_GH_RAW_REPO: &str = "https://raw.githubusercontent.com/odradev/odra";
 
+/// GitHub API endpoint with the latest Odra release.
 pub const ODRA_GITHUB_API_DATA: &str = "https://api.github.com/repos/odradev/odra/releases/latest";
 
+/// crates.io sparse index entry of Odra.
+pub const ODRA_CRATES_IO_INDEX: &str = "https://index.crates.io/od/ra/odra";
+
+/// Env key overriding the template repository, e.g. with a mirror.
+pub const ODRA_TEMPLATE_GH_REPO_ENV_KEY: &str = "CARGO_ODRA_TEMPLATE_REPO";
+
+/// Env key overriding the template raw repository.
+pub const ODRA_TEMPLATE_GH_RAW_REPO_ENV_KEY: &str = "CARGO_ODRA_TEMPLATE_RAW_REPO";
+
+/// Env key overriding the GitHub API endpoint with the latest Odra release.
+pub const ODRA_GITHUB_API_DATA_ENV_KEY: &str = "CARGO_ODRA_GITHUB_API_DATA";
+
+/// Env key overriding the crates.io sparse index entry of Odra.
+pub const ODRA_CRATES_IO_INDEX_ENV_KEY: &str = "CARGO_ODRA_CRATES_IO_INDEX";
+
+/// Env key with a GitHub token used to authenticate requests to GitHub.
+pub const GITHUB_TOKEN_ENV_KEY: &str = "GITHUB_TOKEN";
+
+/// Env keys with a proxy used for http requests, in order of precedence.
+pub const PROXY_ENV_KEYS: [&str; 3] = ["CARGO_ODRA_PROXY", "HTTPS_PROXY", "https_proxy"];
+
+/// Hosts that are sent the GitHub token.
+pub const GITHUB_HOSTS: [&str; 3] = ["github.com", "api.github.com", "raw.githubusercontent.com"];
+
+/// Number of attempts of a http request.
+pub const HTTP_ATTEMPTS: u32 = 3;
+
+/// Delay before the first retry of a http request, doubled with each next one.
+pub const HTTP_RETRY_DELAY_MS: u64 = 500;
+
+/// Timeout of a http request.
+pub const HTTP_TIMEOUT_SECS: u64 = 30;
+
+/// Number of the last lines of a failed command's stderr kept for the error report.
+pub const COMMAND_STDERR_TAIL_LINES: usize = 20;
+
+/// Directory in the target directory, where the commands run by cargo-odra are logged.
+pub const COMMAND_LOGS_DIR: &str = "odra/logs";
+
+/// Env keys recorded with the commands run by cargo-odra, if set.
+pub const COMMAND_LOG_ENV_KEYS: [&str; 7] = [
+    ODRA_MODULE_ENV_KEY,
+    ODRA_BACKEND_ENV_KEY,
+    "CARGO_TARGET_DIR",
+    "CARGO_BUILD_TARGET_DIR",
+    "RUSTFLAGS",
+    "CARGO_ENCODED_RUSTFLAGS",
+    RUSTUP_TOOLCHAIN_ENV_KEY,
+];
+
 /// Default template name.
 pub const ODRA_TEMPLATE_DEFAULT_TEMPLATE: &str = "full";
 
+/// Directory of Odra repository with project templates.
+pub const ODRA_PROJECT_TEMPLATES_DIR: &str = "templates";
+
+/// File in a project template directory describing the template.
+pub const PROJECT_TEMPLATE_METADATA_FILE: &str = "odra-template.toml";
+
+/// Descriptions of the templates that come without a metadata file.
+pub const PROJECT_TEMPLATE_DESCRIPTIONS: [(&str, &str); 3] = [
+    ("full", "A sample contract with tests."),
+    ("blank", "Cargo.toml and the directory structure only."),
+    ("workspace", "A workspace with a sub crate."),
+];
+
 /// Module template.
 pub const MODULE_TEMPLATE: &str = "module";
 
+/// Suffixes of identifiers Odra generates for a contract, e.g. `FlipperHostRef`.
+pub const GENERATED_IDENT_SUFFIXES: [&str; 4] = ["HostRef", "ContractRef", "Deployer", "InitArgs"];
+
+/// Non-deployable module template.
+pub const SUBMODULE_TEMPLATE: &str = "submodule";
+
+/// Event template.
+pub const EVENT_TEMPLATE: &str = "event";
+
+/// Error enum template.
+pub const ERROR_TEMPLATE: &str = "error";
+
+/// Contract tests template.
+pub const TEST_TEMPLATE: &str = "test";
+
 /// Module register snippet.
 pub const MODULE_REGISTER: &str = "module_register";
+
+/// Templates used when the Odra source does not have them, by name.
+pub const BUILT_IN_TEMPLATES: [(&str, &str); 4] = [
+    (SUBMODULE_TEMPLATE, BUILT_IN_SUBMODULE_TEMPLATE),
+    (EVENT_TEMPLATE, BUILT_IN_EVENT_TEMPLATE),
+    (ERROR_TEMPLATE, BUILT_IN_ERROR_TEMPLATE),
+    (TEST_TEMPLATE, BUILT_IN_TEST_TEMPLATE),
+];
+
+/// Built-in non-deployable module template.
+pub const BUILT_IN_SUBMODULE_TEMPLATE: &str = r##"use odra::prelude::*;
+
+/// A module meant to be used by other modules, not deployed on its own.
+#[odra::module]
+pub struct #module_name {
+    value: Var<u32>,
+}
+
+#[odra::module]
+impl #module_name {
+    /// Returns the stored value.
+    pub fn get(&self) -> u32 {
+        self.value.get_or_default()
+    }
+
+    /// Stores a new value.
+    pub fn set(&mut self, value: u32) {
+        self.value.set(value);
+    }
+}
+"##;
+
+/// Built-in event template.
+pub const BUILT_IN_EVENT_TEMPLATE: &str = r##"/// Event emitted by the contract.
+#[odra::event]
+pub struct #event_name {}
+"##;
+
+/// Built-in error enum template.
+pub const BUILT_IN_ERROR_TEMPLATE: &str = r##"/// Errors returned by the contract.
+#[odra::odra_error]
+pub enum #error_name {
+    /// Replace with the errors of the contract.
+    Unknown = 1,
+}
+"##;
+
+/// Built-in contract tests template.
+pub const BUILT_IN_TEST_TEMPLATE: &str = r##"#[cfg(test)]
+mod tests {
+    use super::#contract_nameHostRef;
+    use odra::host::{Deployer, NoArgs};
+
+    #[test]
+    fn deploys() {
+        let env = odra_test::env();
+        let _contract = #contract_nameHostRef::deploy(&env, NoArgs);
+    }
+}
+"##;
+
+/// Env key overriding the cargo-odra cache directory.
+pub const CARGO_ODRA_CACHE_DIR_ENV_KEY: &str = "CARGO_ODRA_CACHE_DIR";
+
+/// Name of the cargo-odra directory inside the user's cache directory.
+pub const CARGO_ODRA_CACHE_DIR: &str = "cargo-odra";
+
+/// Name of the cache directory with Odra source trees, one per version.
+pub const ODRA_SOURCES_CACHE_DIR: &str = "odra";
+
+/// Cargo registry index directories with cached crates.io index entries of Odra.
+pub const ODRA_INDEX_CACHE_GLOB: &str = "registry/index/*/.cache/od/ra/odra";
+
+/// Name of the directory with project-level templates, overriding the upstream ones.
+pub const PROJECT_TEMPLATES_DIR: &str = "templates";
+
+/// Env key disabling colors of the output, see https://no-color.org.
+pub const NO_COLOR_ENV_KEY: &str = "NO_COLOR";
+
+/// Env keys setting the cargo target directory, in the order of precedence.
+pub const CARGO_TARGET_DIR_ENV_KEYS: [&str; 2] = ["CARGO_TARGET_DIR", "CARGO_BUILD_TARGET_DIR"];
+
+/// Cargo config files in a `.cargo` directory. When both exist, cargo reads the first one.
+pub const CARGO_CONFIG_FILES: [&str; 2] = ["config", "config.toml"];
+
+/// Rustup toolchain env key.
+pub const RUSTUP_TOOLCHAIN_ENV_KEY: &str = "RUSTUP_TOOLCHAIN";
+
+/// Source date epoch env key.
+pub const SOURCE_DATE_EPOCH_ENV_KEY: &str = "SOURCE_DATE_EPOCH";
+
+/// Toolchain files, in the order rustup looks for them.
+pub const RUST_TOOLCHAIN_FILES: [&str; 2] = ["rust-toolchain", "rust-toolchain.toml"];
+
+/// Prefix replacing the project root in paths embedded into reproducible builds.
+pub const REPRODUCIBLE_PROJECT_PREFIX: &str = "/odra/project";
+
+/// Prefix replacing the cargo home in paths embedded into reproducible builds.
+pub const REPRODUCIBLE_CARGO_HOME_PREFIX: &str = "/odra/cargo";
+
+/// Name of the build descriptor inside a source verification bundle.
+pub const VERIFICATION_DESCRIPTOR_FILE: &str = "odra-build.json";
+
+/// Entries of a package included into a source verification bundle, like the ones
+/// `cargo package` takes.
+pub const VERIFICATION_BUNDLE_PACKAGE_ENTRIES: [&str; 4] = ["Cargo.toml", "build.rs", "src", "bin"];
+
+/// Files of the `.cargo` directory never included into a source verification bundle.
+pub const VERIFICATION_BUNDLE_EXCLUDED_FILES: [&str; 2] = ["credentials", "credentials.toml"];
+
+/// Directory of the vendored dependencies inside a source verification bundle.
+pub const VERIFICATION_BUNDLE_VENDOR_DIR: &str = "vendor";
+
+/// Odra crates that are workspace dependencies of converted projects.
+pub const ODRA_CRATES: [&str; 3] = ["odra", "odra-test", "odra-build"];
+
+/// Keys of a Cargo.toml dependency telling where the dependency comes from.
+pub const CARGO_DEPENDENCY_LOCATION_KEYS: [&str; 7] =
+    ["version", "path", "git", "branch", "tag", "rev", "registry"];
+
+/// Kinds of dependencies tables of Cargo.toml.
+pub const CARGO_DEPENDENCIES_TABLES: [&str; 3] =
+    ["dependencies", "dev-dependencies", "build-dependencies"];
+
+/// Cargo.toml tables allowed only in the workspace root.
+pub const CARGO_WORKSPACE_ROOT_TABLES: [&str; 3] = ["profile", "patch", "replace"];
+
+/// Project entries that stay in the workspace root when a project is converted into one.
+pub const WORKSPACE_ROOT_ENTRIES: [&str; 13] = [
+    "Cargo.toml",
+    "Cargo.lock",
+    "Odra.toml",
+    "target",
+    "wasm",
+    ".git",
+    ".gitignore",
+    ".github",
+    ".cargo",
+    "rust-toolchain",
+    "rust-toolchain.toml",
+    "README.md",
+    "LICENSE",
+];
+
+/// Manifest of the project templates, renamed to Cargo.toml after generation.
+pub const ODRA_TEMPLATE_CARGO_TOML: &str = "_Cargo.toml";
+
+/// Placeholders of the Odra dependencies in the project templates, with the crates they
+/// stand for.
+pub const ODRA_TEMPLATE_DEPENDENCY_PLACEHOLDERS: [(&str, &str); 3] = [
+    ("#odra_dependency", "odra"),
+    ("#odra_test_dependency", "odra-test"),
+    ("#odra_build_dependency", "odra-build"),
+];
+
+/// Build binaries of Odra crates, as laid out in the project templates.
+pub const ODRA_TEMPLATE_BINS: [&str; 2] = ["bin/build_contract.rs", "bin/build_schema.rs"];
+
+/// Placeholders of the crate name in the project templates.
+pub const ODRA_TEMPLATE_CRATE_NAME_PLACEHOLDERS: [&str; 2] = ["{{project-name}}", "{{crate_name}}"];
+
+/// Changelog file of the Odra repository.
+pub const ODRA_CHANGELOG_FILE: &str = "CHANGELOG.md";
@@ -1,14 +1,22 @@
 //! Errors.
 
-use std::{path::PathBuf, process::exit};
+use std::{
+    error::Error as StdError,
+    io,
+    path::PathBuf,
+    process::{exit, ExitStatus},
+};
 
-use crate::log;
+use crate::{command, command_log, log};
+
+/// Underlying error of a failure, like an io or an http error.
+pub type Cause = Box<dyn StdError + Send + Sync>;
 
 /// Errors enum.
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
-    #[error("Command {0} failed.")]
-    CommandFailed(String),
+    #[error("Command `{0}` failed.")]
+    CommandFailed(String, #[source] CommandError),
 
     #[error("Invalid command {0}.")]
     InvalidInternalCommand(String),
@@ -16,17 +24,17 @@ pub enum Error {
     #[error("Failed to read Cargo.toml: {0}.")]
     FailedToReadCargo(String),
 
-    #[error("wasm32-unknown-unknown target is not present, install it by executing:\nrustup target add wasm32-unknown-unknown")]
+    #[error("wasm32-unknown-unknown target is not installed.")]
     WasmTargetNotInstalled,
 
     #[error("This command can be executed only in folder with Odra project.")]
     NotAnOdraProject,
 
-    #[error("There was an error while running wasm-strip - is it installed?")]
-    WasmstripDidNotFinish,
+    #[error("There was an error while running wasm-strip.")]
+    WasmstripDidNotFinish(#[source] CommandError),
 
-    #[error("There was an error while running wasm-opt - is it installed?")]
-    WasmoptDidNotFinish,
+    #[error("There was an error while running wasm-opt.")]
+    WasmoptDidNotFinish(#[source] CommandError),
 
     #[error("Current directory is not empty.")]
     CurrentDirIsNotEmpty,
@@ -38,7 +46,7 @@ pub enum Error {
     ContractAlreadyInOdraToml(String),
 
     #[error("Removing {0} directory failed.")]
-    RemoveDirNotPossible(PathBuf),
+    RemoveDirNotPossible(PathBuf, #[source] io::Error),
 
     #[error("Module {0} not found.")]
     ModuleNotFound(String),
@@ -46,8 +54,8 @@ pub enum Error {
     #[error("Odra.toml not found at location {0}")]
     OdraTomlNotFound(PathBuf),
 
-    #[error("Failed to fetch template: {0}")]
-    FailedToFetchTemplate(String),
+    #[error("Failed to fetch template {0}.")]
+    FailedToFetchTemplate(String, #[source] Cause),
 
     #[error("Failed to parse template: {0}")]
     FailedToParseTemplate(String),
@@ -56,7 +64,7 @@ pub enum Error {
     CouldNotDetermineCurrentDirectory,
 
     #[error("Contract {0} not found in Odra.toml")]
-    ContractNotFound(String),
+    ContractNotFound(String, Vec<String>),
 
     #[error("Odra is not a dependency of this project.")]
     OdraNotADependency,
@@ -78,42 +86,271 @@ pub enum Error {
 
     #[error("Project is a workspace, module name is required")]
     ModuleNotProvided,
+
+    #[error("Toolchain file not found in {0}, it is required for reproducible builds.")]
+    ToolchainFileNotFound(PathBuf),
+
+    #[error("Could not read the toolchain from {0}.")]
+    MalformedToolchainFile(PathBuf),
+
+    #[error("Wasm file {0} not found.")]
+    WasmFileNotFound(PathBuf),
+
+    #[error("Wasm of {0} does not match the rebuilt one.\nExpected: {1}\nRebuilt:  {2}")]
+    WasmHashMismatch(String, String, String),
+
+    #[error("Failed to create verification bundle: {0}")]
+    FailedToCreateBundle(String),
+
+    #[error("Template {0} for {1} is not cached, run the command without --offline first.")]
+    TemplateNotCached(String, String),
+
+    #[error("{0} already exists in {1}.")]
+    ItemAlreadyExists(String, PathBuf),
+
+    #[error("Could not find the #[odra::module] attribute of {0}.")]
+    ModuleAttributeNotFound(String),
+
+    #[error("Failed to parse {0}: {1}")]
+    FailedToParseRustFile(PathBuf, String),
+
+    #[error("Removing {0} file failed.")]
+    RemoveFileNotPossible(PathBuf, #[source] io::Error),
+
+    #[error("Failed to parse {0}: {1}")]
+    FailedToParseOdraToml(PathBuf, String),
+
+    #[error("Odra.toml does not match the code, found {0} problem(s).")]
+    OdraTomlCheckFailed(usize),
+
+    #[error("Failed to write {0}.")]
+    FailedToWriteFile(PathBuf, #[source] io::Error),
+
+    #[error("Failed to copy {0} to {1}.")]
+    FailedToCopyFile(PathBuf, PathBuf, #[source] io::Error),
+
+    #[error("Failed to rename {0} to {1}.")]
+    FailedToRenameFile(PathBuf, PathBuf, #[source] io::Error),
+
+    #[error("Failed to create {0} directory.")]
+    FailedToCreateDir(PathBuf, #[source] io::Error),
+
+    #[error("Failed to read {0}.")]
+    FailedToReadFile(PathBuf, #[source] io::Error),
+
+    #[error("Template {0} not found. {1}")]
+    ProjectTemplateNotFound(String, String),
+
+    #[error("Odra {0} is not cached, run `cargo odra templates fetch {0}` first.")]
+    OdraNotCached(String),
+
+    #[error("Odra source {0} is not available offline, use a cached version instead.")]
+    SourceNotAvailableOffline(String),
+
+    #[error("Could not determine the latest version of Odra: {0}")]
+    FailedToResolveOdraVersion(String),
+
+    #[error("Request to {0} failed: {1}.")]
+    HttpRequestFailed(String, String),
+
+    #[error("{0} is already a workspace.")]
+    AlreadyAWorkspace(PathBuf),
+
+    #[error("{0} is not a workspace, run `cargo odra workspace convert` first.")]
+    NotAWorkspace(PathBuf),
 }
 
 impl Error {
     /// Returns error code.
     pub fn code(&self) -> i32 {
         match self {
-            Error::CommandFailed(_) => 1,
+            Error::CommandFailed(_, _) => 1,
             Error::InvalidInternalCommand(_) => 2,
             Error::FailedToReadCargo(_) => 3,
             Error::WasmTargetNotInstalled => 4,
             Error::NotAnOdraProject => 5,
-            Error::WasmstripDidNotFinish => 6,
+            Error::WasmstripDidNotFinish(_) => 6,
             Error::CurrentDirIsNotEmpty => 7,
             Error::FileAlreadyExists(_) => 8,
             Error::ContractAlreadyInOdraToml(_) => 9,
-            Error::RemoveDirNotPossible(_) => 10,
+            Error::RemoveDirNotPossible(_, _) => 10,
             Error::ModuleNotFound(_) => 11,
             Error::OdraTomlNotFound(_) => 12,
-            Error::FailedToFetchTemplate(_) => 14,
+            Error::FailedToFetchTemplate(_, _) => 14,
             Error::FailedToParseTemplate(_) => 15,
             Error::CouldNotDetermineCurrentDirectory => 16,
-            Error::ContractNotFound(_) => 17,
+            Error::ContractNotFound(_, _) => 17,
             Error::OdraNotADependency => 18,
             Error::FailedToGenerateProjectFromTemplate(_) => 19,
             Error::FailedToParseArgument(_) => 20,
             Error::MalformedFqn => 21,
             Error::LibRsNotFound => 22,
             Error::ModuleAlreadyInLibRs(_) => 23,
-            Error::WasmoptDidNotFinish => 24,
+            Error::WasmoptDidNotFinish(_) => 24,
             Error::ModuleNotProvided => 25,
+            Error::ToolchainFileNotFound(_) => 26,
+            Error::MalformedToolchainFile(_) => 27,
+            Error::WasmFileNotFound(_) => 28,
+            Error::WasmHashMismatch(_, _, _) => 29,
+            Error::FailedToCreateBundle(_) => 30,
+            Error::TemplateNotCached(_, _) => 31,
+            Error::ItemAlreadyExists(_, _) => 32,
+            Error::ModuleAttributeNotFound(_) => 33,
+            Error::FailedToParseRustFile(_, _) => 34,
+            Error::RemoveFileNotPossible(_, _) => 35,
+            Error::FailedToParseOdraToml(_, _) => 36,
+            Error::OdraTomlCheckFailed(_) => 37,
+            Error::FailedToWriteFile(_, _) => 38,
+            Error::FailedToCopyFile(_, _, _) => 39,
+            Error::FailedToRenameFile(_, _, _) => 40,
+            Error::FailedToCreateDir(_, _) => 41,
+            Error::FailedToReadFile(_, _) => 42,
+            Error::ProjectTemplateNotFound(_, _) => 43,
+            Error::OdraNotCached(_) => 44,
+            Error::SourceNotAvailableOffline(_) => 45,
+            Error::FailedToResolveOdraVersion(_) => 46,
+            Error::HttpRequestFailed(_, _) => 47,
+            Error::AlreadyAWorkspace(_) => 48,
+            Error::NotAWorkspace(_) => 49,
         }
     }
 
-    /// Logs error message and exits with the given error code.
+    /// Returns a suggestion on how to fix the error, if there is one.
+    pub fn hint(&self) -> Option<String> {
+        let hint = match self {
+            Error::WasmTargetNotInstalled => {
+                "Install it with `rustup target add wasm32-unknown-unknown`.".to_string()
+            }
+            Error::WasmoptDidNotFinish(CommandError::NotStarted(_)) => {
+                "Install binaryen, e.g. with `cargo install wasm-opt`, \
+                `brew install binaryen` or `apt install binaryen`."
+                    .to_string()
+            }
+            Error::WasmstripDidNotFinish(CommandError::NotStarted(_)) => {
+                "Install wabt, e.g. with `brew install wabt` or `apt install wabt`.".to_string()
+            }
+            Error::CommandFailed(_, CommandError::NotStarted(_)) => {
+                "Make sure cargo is installed and in PATH, see https://rustup.rs.".to_string()
+            }
+            Error::NotAnOdraProject => {
+                "Run it in a directory with Odra.toml, or create a project with `cargo odra new`."
+                    .to_string()
+            }
+            Error::CurrentDirIsNotEmpty => {
+                "Use `cargo odra new` to create the project in a new directory.".to_string()
+            }
+            Error::ContractNotFound(_, available) if available.is_empty() => {
+                "Odra.toml has no contracts, add them with `cargo odra discover --write` \
+                or `cargo odra generate`."
+                    .to_string()
+            }
+            Error::ContractNotFound(_, available) => {
+                format!("Available contracts: {}.", available.join(", "))
+            }
+            Error::OdraNotADependency => {
+                "Add odra to [dependencies] or [workspace.dependencies] of Cargo.toml.".to_string()
+            }
+            Error::ModuleNotProvided => "Pass the crate with --module.".to_string(),
+            Error::ToolchainFileNotFound(_) => {
+                "Pin the toolchain in a rust-toolchain.toml file.".to_string()
+            }
+            Error::OdraTomlCheckFailed(_) => {
+                "Run `cargo odra discover --write` to add the contracts found in the code."
+                    .to_string()
+            }
+            Error::FailedToResolveOdraVersion(_) => {
+                "Pass the version with --source, or use a cached one with --offline.".to_string()
+            }
+            Error::CommandFailed(_, err)
+            | Error::WasmoptDidNotFinish(err)
+            | Error::WasmstripDidNotFinish(err) => return err.hint(),
+            // Templates are cloned with git, other causes are failed downloads.
+            Error::FailedToFetchTemplate(_, cause) => match cause.downcast_ref() {
+                Some(CommandError::NotStarted(_)) => {
+                    "Make sure git is installed and in PATH, see https://git-scm.com/downloads."
+                        .to_string()
+                }
+                Some(err) => return err.hint(),
+                None => return None,
+            },
+            Error::HttpRequestFailed(_, _) => {
+                "Check the network connection, behind a proxy set CARGO_ODRA_PROXY.".to_string()
+            }
+            Error::AlreadyAWorkspace(_) => {
+                "Add crates with `cargo odra workspace add-member`.".to_string()
+            }
+            _ => return None,
+        };
+        Some(hint)
+    }
+
+    /// Returns messages of the errors that caused this one, starting with the closest.
+    pub fn causes(&self) -> Vec<String> {
+        let mut causes = vec![];
+        let mut source = self.source();
+        while let Some(cause) = source {
+            causes.push(cause.to_string());
+            source = cause.source();
+        }
+        causes
+    }
+
+    /// Logs error message with its causes and hint, and exits with the given error code.
+    ///
+    /// Only the closest cause is printed, unless in verbose mode.
     pub fn print_and_die(&self) -> ! {
         log::error(self.to_string());
+        let causes = self.causes();
+        let shown = match command::verbosity_arg() {
+            Some("--verbose") => causes.len(),
+            _ => causes.len().min(1),
+        };
+        causes
+            .iter()
+            .take(shown)
+            .enumerate()
+            .for_each(|(index, cause)| {
+                log::cause(cause, index + 1 == shown);
+            });
+        if shown < causes.len() {
+            log::note("Run with --verbose to see all causes.");
+        }
+        if let Some(hint) = self.hint() {
+            log::hint(hint);
+        }
         exit(self.code());
     }
 }
+
+/// Cause of a failed external command.
+#[derive(Debug, thiserror::Error)]
+pub enum CommandError {
+    #[error("It could not be started.")]
+    NotStarted(#[source] io::Error),
+
+    /// Exit status, command line reproducing the failure and the end of its stderr.
+    #[error("It exited with {0}.")]
+    Exited(ExitStatus, String, #[source] Option<CommandStderr>),
+}
+
+impl CommandError {
+    /// Returns a hint with the command line reproducing the failure.
+    fn hint(&self) -> Option<String> {
+        let CommandError::Exited(_, reproduction, _) = self else {
+            return None;
+        };
+        let mut hint = format!("To reproduce it, run:\n{reproduction}");
+        if let Some(log_file) = command_log::log_file() {
+            hint.push_str(&format!(
+                "\nCommands run by cargo-odra are logged to {}.",
+                log_file.display()
+            ));
+        }
+        Some(hint)
+    }
+}
+
+/// Last lines written to stderr by a failed command.
+#[derive(Debug, thiserror::Error)]
+#[error("Its output ended with:\n{0}")]
+pub struct CommandStderr(pub String);
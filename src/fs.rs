@@ -0,0 +1,94 @@
+//! Module performing filesystem changes. In dry-run mode the changes are only logged.
+
+use std::{
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    path::Path,
+};
+
+use crate::{command, errors::Error, log};
+
+/// Writes a content to a file, creating or truncating it.
+pub fn write(path: &Path, content: &str) -> Result<(), Error> {
+    if dry_run(format!("Would write {}", path.display())) {
+        return Ok(());
+    }
+    fs::write(path, content).map_err(|err| Error::FailedToWriteFile(path.to_path_buf(), err))
+}
+
+/// Appends a content to an existing file.
+pub fn append(path: &Path, content: &str) -> Result<(), Error> {
+    if dry_run(format!("Would append to {}", path.display())) {
+        return Ok(());
+    }
+    OpenOptions::new()
+        .append(true)
+        .open(path)
+        .and_then(|mut file| file.write_all(content.as_bytes()))
+        .map_err(|err| Error::FailedToWriteFile(path.to_path_buf(), err))
+}
+
+/// Copies a file.
+pub fn copy(source: &Path, target: &Path) -> Result<(), Error> {
+    if dry_run(format!(
+        "Would copy {} to {}",
+        source.display(),
+        target.display()
+    )) {
+        return Ok(());
+    }
+    fs::copy(source, target)
+        .map(|_| ())
+        .map_err(|err| Error::FailedToCopyFile(source.to_path_buf(), target.to_path_buf(), err))
+}
+
+/// Renames a file.
+pub fn rename(source: &Path, target: &Path) -> Result<(), Error> {
+    if dry_run(format!(
+        "Would rename {} to {}",
+        source.display(),
+        target.display()
+    )) {
+        return Ok(());
+    }
+    fs::rename(source, target)
+        .map_err(|err| Error::FailedToRenameFile(source.to_path_buf(), target.to_path_buf(), err))
+}
+
+/// Creates a directory with all its parents, if it does not exist.
+pub fn create_dir(path: &Path) -> Result<(), Error> {
+    if path.is_dir() || dry_run(format!("Would create {}", path.display())) {
+        return Ok(());
+    }
+    fs::create_dir_all(path).map_err(|err| Error::FailedToCreateDir(path.to_path_buf(), err))
+}
+
+/// Removes a file.
+pub fn remove_file(path: &Path) -> Result<(), Error> {
+    if dry_run(format!("Would remove {}", path.display())) {
+        return Ok(());
+    }
+    fs::remove_file(path).map_err(|err| Error::RemoveFileNotPossible(path.to_path_buf(), err))
+}
+
+/// Removes a directory with its content. Succeeds if the directory does not exist.
+pub fn remove_dir(path: &Path) -> Result<(), Error> {
+    if !path.exists() || dry_run(format!("Would remove {}", path.display())) {
+        return Ok(());
+    }
+    match fs::remove_dir_all(path) {
+        Err(err) if err.kind() != io::ErrorKind::NotFound => {
+            Err(Error::RemoveDirNotPossible(path.to_path_buf(), err))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Logs the message and returns true in dry-run mode.
+fn dry_run(message: String) -> bool {
+    let dry_run = command::is_dry_run();
+    if dry_run {
+        log::info(message);
+    }
+    dry_run
+}
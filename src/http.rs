@@ -0,0 +1,149 @@
+//! Module performing http requests to GitHub and crates.io.
+
+use std::{env, thread, time::Duration};
+
+use ureq::{serde_json::Value, Agent, AgentBuilder, Proxy, Response};
+
+use crate::{
+    consts::{
+        GITHUB_HOSTS,
+        GITHUB_TOKEN_ENV_KEY,
+        HTTP_ATTEMPTS,
+        HTTP_RETRY_DELAY_MS,
+        HTTP_TIMEOUT_SECS,
+        ODRA_CRATES_IO_INDEX,
+        ODRA_CRATES_IO_INDEX_ENV_KEY,
+        ODRA_GITHUB_API_DATA,
+        ODRA_GITHUB_API_DATA_ENV_KEY,
+        ODRA_TEMPLATE_GH_RAW_REPO,
+        ODRA_TEMPLATE_GH_RAW_REPO_ENV_KEY,
+        ODRA_TEMPLATE_GH_REPO,
+        ODRA_TEMPLATE_GH_REPO_ENV_KEY,
+        PROXY_ENV_KEYS,
+    },
+    errors::Error,
+    log,
+};
+
+/// Returns the template repository, or its mirror if set.
+pub fn template_repo() -> String {
+    env_or(ODRA_TEMPLATE_GH_REPO_ENV_KEY, ODRA_TEMPLATE_GH_REPO)
+}
+
+/// Returns the template raw repository, or its mirror if set.
+pub fn template_raw_repo() -> String {
+    env_or(ODRA_TEMPLATE_GH_RAW_REPO_ENV_KEY, ODRA_TEMPLATE_GH_RAW_REPO)
+}
+
+/// Returns the GitHub API endpoint with the latest Odra release, or its mirror if set.
+pub fn github_api_data() -> String {
+    env_or(ODRA_GITHUB_API_DATA_ENV_KEY, ODRA_GITHUB_API_DATA)
+}
+
+/// Returns the crates.io sparse index entry of Odra, or its mirror if set.
+pub fn crates_io_index() -> String {
+    env_or(ODRA_CRATES_IO_INDEX_ENV_KEY, ODRA_CRATES_IO_INDEX)
+}
+
+/// Returns the proxy to use, if any is set.
+pub fn proxy() -> Option<String> {
+    PROXY_ENV_KEYS
+        .iter()
+        .find_map(|key| env::var(key).ok().filter(|value| !value.is_empty()))
+}
+
+/// Sends a GET request, retrying with a backoff on connection errors, server errors
+/// and too many requests. Requests to GitHub are authenticated with `GITHUB_TOKEN`, if set.
+pub fn get(url: &str) -> Result<Response, Error> {
+    let agent = agent(url)?;
+    let token = github_token(url);
+    let mut delay = Duration::from_millis(HTTP_RETRY_DELAY_MS);
+    let mut attempt = 1;
+    loop {
+        let mut request = agent.get(url);
+        if let Some(token) = &token {
+            request = request.set("Authorization", &format!("Bearer {token}"));
+        }
+        match request.call() {
+            Ok(response) => return Ok(response),
+            Err(err) if attempt < HTTP_ATTEMPTS && is_transient(&err) => {
+                log::verbose(format!("Request to {url} failed ({err}), retrying..."));
+                thread::sleep(delay);
+                delay *= 2;
+                attempt += 1;
+            }
+            Err(err) => return Err(Error::HttpRequestFailed(url.to_string(), reason(err))),
+        }
+    }
+}
+
+/// Sends a GET request and returns the response body.
+pub fn get_string(url: &str) -> Result<String, Error> {
+    get(url)?
+        .into_string()
+        .map_err(|err| Error::HttpRequestFailed(url.to_string(), err.to_string()))
+}
+
+/// Sends a GET request and parses the response body as json.
+pub fn get_json(url: &str) -> Result<Value, Error> {
+    get(url)?
+        .into_json()
+        .map_err(|err| Error::HttpRequestFailed(url.to_string(), format!("invalid json ({err})")))
+}
+
+fn agent(url: &str) -> Result<Agent, Error> {
+    let mut builder = AgentBuilder::new().timeout(Duration::from_secs(HTTP_TIMEOUT_SECS));
+    if let Some(proxy) = proxy() {
+        let proxy = Proxy::new(&proxy).map_err(|err| {
+            Error::HttpRequestFailed(url.to_string(), format!("invalid proxy {proxy} ({err})"))
+        })?;
+        builder = builder.proxy(proxy);
+    }
+    Ok(builder.build())
+}
+
+/// Returns the GitHub token, if set and the url points to GitHub. Mirrors do not get it.
+fn github_token(url: &str) -> Option<String> {
+    let host = url
+        .split("://")
+        .nth(1)
+        .and_then(|rest| rest.split(['/', ':']).next())
+        .unwrap_or_default();
+    if !GITHUB_HOSTS.contains(&host) {
+        return None;
+    }
+    env::var(GITHUB_TOKEN_ENV_KEY)
+        .ok()
+        .filter(|token| !token.is_empty())
+}
+
+fn is_transient(err: &ureq::Error) -> bool {
+    match err {
+        ureq::Error::Status(status, _) => *status == 429 || *status >= 500,
+        ureq::Error::Transport(_) => true,
+    }
+}
+
+fn reason(err: ureq::Error) -> String {
+    match err {
+        ureq::Error::Status(403 | 429, response)
+            if response.header("x-ratelimit-remaining") == Some("0") =>
+        {
+            format!("GitHub API rate limit exceeded, set {GITHUB_TOKEN_ENV_KEY} to raise it")
+        }
+        ureq::Error::Status(status, response) => {
+            format!("status {status} {}", response.status_text())
+        }
+        ureq::Error::Transport(transport) => match transport.message() {
+            Some(message) => format!("{}: {message}", transport.kind()),
+            None => transport.kind().to_string(),
+        },
+    }
+}
+
+fn env_or(key: &str, default: &str) -> String {
+    env::var(key)
+        .ok()
+        .filter(|value| !value.is_empty())
+        .unwrap_or_else(|| default.to_string())
+}
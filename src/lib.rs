@@ -5,13 +5,22 @@
 //! [Github Page](https://github.com/odradev/cargo-odra).
 
 mod actions;
+mod build_options;
 mod cargo_toml;
 pub mod cli;
 mod command;
+mod command_log;
 mod consts;
 mod errors;
+mod fs;
+mod http;
+mod lib_rs;
 mod log;
 mod odra_toml;
+mod odra_version;
 mod paths;
 mod project;
+mod reproducible;
+mod sources;
 mod template;
+mod template_tree;
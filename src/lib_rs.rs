@@ -0,0 +1,219 @@
+//! Module for editing src/lib.rs files.
+
+use std::path::{Path, PathBuf};
+
+use quote::ToTokens;
+use syn::{spanned::Spanned, AttrStyle, Item, UseTree};
+
+use crate::{command, errors::Error};
+
+/// Parsed src/lib.rs file.
+pub struct LibRs {
+    path: PathBuf,
+    content: String,
+    file: syn::File,
+}
+
+impl LibRs {
+    /// Loads and parses src/lib.rs file.
+    pub fn load(path: &Path) -> LibRs {
+        let content = command::read_file_content(path.to_path_buf())
+            .unwrap_or_else(|_| Error::LibRsNotFound.print_and_die());
+        let file = parse(path, &content).unwrap_or_else(|err| err.print_and_die());
+        LibRs {
+            path: path.to_path_buf(),
+            content,
+            file,
+        }
+    }
+
+    /// Checks if a module with the given name is declared, whether it is public,
+    /// behind a `cfg` attribute or inline.
+    pub fn has_module(&self, module_name: &str) -> bool {
+        self.file
+            .items
+            .iter()
+            .any(|item| matches!(item, Item::Mod(module) if module.ident == module_name))
+    }
+
+    /// Inserts items of a code snippet after the last `mod` or `use` declaration,
+    /// skipping the ones already present. Returns the inserted code.
+    pub fn insert(&mut self, snippet: &str) -> String {
+        let snippet_file = parse(&self.path, snippet).unwrap_or_else(|err| err.print_and_die());
+        let snippet_lines: Vec<&str> = snippet.lines().collect();
+        let new_items: Vec<String> = snippet_file
+            .items
+            .iter()
+            .filter(|item| !self.contains(item))
+            .map(|item| {
+                let span = item.span();
+                snippet_lines[span.start().line - 1..span.end().line].join("\n")
+            })
+            .collect();
+        if new_items.is_empty() {
+            return String::new();
+        }
+        let code = new_items.join("\n");
+
+        let mut lines: Vec<&str> = self.content.lines().collect();
+        let (line, code_with_spacing) = match self.last_declaration_line() {
+            Some(line) => (line, code.clone()),
+            None => match self.last_inner_attribute_line() {
+                Some(line) => (line, format!("\n{code}")),
+                None if lines.is_empty() => (0, code.clone()),
+                None => (0, format!("{code}\n")),
+            },
+        };
+        lines.insert(line, &code_with_spacing);
+
+        self.content = format!("{}\n", lines.join("\n"));
+        self.file = parse(&self.path, &self.content).unwrap_or_else(|err| err.print_and_die());
+        code
+    }
+
+    /// Removes declaration of a module along with `use` items importing from it.
+    /// Returns the removed code.
+    pub fn remove_module(&mut self, module_name: &str) -> String {
+        let removed_lines: Vec<(usize, usize)> = self
+            .module_items(module_name)
+            .map(|item| (item.span().start().line, item.span().end().line))
+            .collect();
+
+        let is_removed = |line: usize| {
+            removed_lines
+                .iter()
+                .any(|(start, end)| (*start..=*end).contains(&line))
+        };
+        let (removed, kept): (Vec<_>, Vec<_>) = self
+            .content
+            .lines()
+            .enumerate()
+            .partition(|(index, _)| is_removed(index + 1));
+        let join = |lines: Vec<(usize, &str)>| {
+            lines
+                .into_iter()
+                .map(|(_, line)| line)
+                .collect::<Vec<_>>()
+                .join("\n")
+        };
+
+        let removed = join(removed);
+        self.content = format!("{}\n", join(kept));
+        self.file = parse(&self.path, &self.content).unwrap_or_else(|err| err.print_and_die());
+        removed
+    }
+
+    /// Rewrites `mod` and `use` items of a module with the given function.
+    /// Returns true if any item was changed.
+    pub fn edit_module(&mut self, module_name: &str, edit: impl Fn(&str) -> String) -> bool {
+        let edited_lines: Vec<(usize, usize)> = self
+            .module_items(module_name)
+            .map(|item| (item.span().start().line, item.span().end().line))
+            .collect();
+
+        let mut changed = false;
+        let lines: Vec<String> = self
+            .content
+            .lines()
+            .enumerate()
+            .map(|(index, line)| {
+                let is_edited = edited_lines
+                    .iter()
+                    .any(|(start, end)| (*start..=*end).contains(&(index + 1)));
+                if !is_edited {
+                    return line.to_string();
+                }
+                let edited = edit(line);
+                changed |= edited != line;
+                edited
+            })
+            .collect();
+
+        self.content = format!("{}\n", lines.join("\n"));
+        self.file = parse(&self.path, &self.content).unwrap_or_else(|err| err.print_and_die());
+        changed
+    }
+
+    /// Saves the file.
+    pub fn save(&self) {
+        command::write_to_file(self.path.clone(), &self.content);
+    }
+
+    /// Returns the declaration of a module and `use` items importing from it.
+    fn module_items<'a>(&'a self, module_name: &'a str) -> impl Iterator<Item = &'a Item> {
+        self.file.items.iter().filter(move |item| match item {
+            Item::Mod(module) => module.ident == module_name,
+            Item::Use(item_use) => uses_module(&item_use.tree, module_name),
+            _ => false,
+        })
+    }
+
+    /// Checks if the same item is already in the file. Modules are compared by name only.
+    fn contains(&self, item: &Item) -> bool {
+        match item {
+            Item::Mod(module) => self.has_module(&module.ident.to_string()),
+            item => {
+                let tokens = item.to_token_stream().to_string();
+                self.file
+                    .items
+                    .iter()
+                    .any(|existing| existing.to_token_stream().to_string() == tokens)
+            }
+        }
+    }
+
+    /// Returns the line on which the last top-level `mod`, `use` or `extern crate`
+    /// declaration ends.
+    fn last_declaration_line(&self) -> Option<usize> {
+        self.file
+            .items
+            .iter()
+            .filter(|item| match item {
+                Item::Mod(module) => module.content.is_none(),
+                Item::Use(_) | Item::ExternCrate(_) => true,
+                _ => false,
+            })
+            .map(|item| item.span().end().line)
+            .max()
+    }
+
+    /// Returns the line on which the last inner attribute, like `#![no_std]` or `//!`
+    /// documentation, ends.
+    fn last_inner_attribute_line(&self) -> Option<usize> {
+        self.file
+            .attrs
+            .iter()
+            .filter(|attr| matches!(attr.style, AttrStyle::Inner(_)))
+            .map(|attr| attr.span().end().line)
+            .max()
+    }
+}
+
+/// Checks if a `use` tree starts with the given module, also via `self::` or `crate::`.
+fn uses_module(tree: &UseTree, module_name: &str) -> bool {
+    match tree {
+        UseTree::Path(path) if path.ident == "self" || path.ident == "crate" => {
+            uses_module(&path.tree, module_name)
+        }
+        UseTree::Path(path) => path.ident == module_name,
+        UseTree::Name(name) => name.ident == module_name,
+        UseTree::Rename(rename) => rename.ident == module_name,
+        _ => false,
+    }
+}
+
+/// Parses a Rust file, reporting the position of a syntax error.
+pub fn parse(path: &Path, content: &str) -> Result<syn::File, Error> {
+    syn::parse_file(content).map_err(|err| {
+        let start = err.span().start();
+        Error::FailedToParseRustFile(
+            path.to_path_buf(),
+            format!(
+                "{} at line {}, column {}",
+                err,
+                start.line,
+                start.column + 1
+            ),
+        )
+    })
+}
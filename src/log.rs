@@ -1,18 +1,79 @@
 //! Logging functions
 
-/// Info message.
+use std::{
+    env,
+    io::{self, IsTerminal},
+};
+
+use crate::{command, consts::NO_COLOR_ENV_KEY};
+
+/// Info message, not printed in quiet mode.
 pub fn info<T: AsRef<str>>(message: T) {
-    prettycli::info(message.as_ref());
+    if command::verbosity_arg() != Some("--quiet") {
+        prettycli::info(message.as_ref());
+    }
+}
+
+/// Plain text, like a changelog, not printed in quiet mode.
+pub fn text<T: AsRef<str>>(message: T) {
+    if command::verbosity_arg() != Some("--quiet") {
+        println!("{}", message.as_ref());
+    }
 }
 
-/// Warning message, not used yet - remove underscore when in use.
+/// Info message, printed only in verbose mode.
+pub fn verbose<T: AsRef<str>>(message: T) {
+    if command::verbosity_arg() == Some("--verbose") {
+        info(message);
+    }
+}
+
+/// Warning message.
 pub fn warn<T: AsRef<str>>(message: T) {
     prettycli::warn(message.as_ref());
 }
 
-/// Error message.
+/// Error message, printed to stderr in the format of `prettycli::error`.
 pub fn error<T: AsRef<str>>(message: T) {
-    prettycli::error(message.as_ref());
+    eprintln!(
+        "🤦  {} {}",
+        paint("1;31", "ERROR :"),
+        paint("31", message.as_ref())
+    );
+}
+
+/// Cause of an error, printed below it to stderr. Multiline causes are indented.
+pub fn cause<T: AsRef<str>>(message: T, last: bool) {
+    let branch = if last { "╰─▶" } else { "├─▶" };
+    let indent = if last { "\n        " } else { "\n    │   " };
+    eprintln!(
+        "    {} {}",
+        paint("31", branch),
+        message.as_ref().replace('\n', indent)
+    );
+}
+
+/// Note printed below an error to stderr.
+pub fn note<T: AsRef<str>>(message: T) {
+    eprintln!("    {}", paint("2", message.as_ref()));
+}
+
+/// Hint on how to fix an error, printed below it to stderr.
+pub fn hint<T: AsRef<str>>(message: T) {
+    eprintln!(
+        "    {} {}",
+        paint("1;36", "help:"),
+        message.as_ref().replace('\n', "\n          ")
+    );
+}
+
+/// Colors text printed to stderr, unless it is not a terminal or colors are disabled.
+fn paint(color: &str, text: &str) -> String {
+    let no_color = env::var_os(NO_COLOR_ENV_KEY).map_or(false, |value| !value.is_empty());
+    match io::stderr().is_terminal() && !no_color {
+        true => format!("\x1B[{color}m{text}\x1B[0m"),
+        false => text.to_string(),
+    }
 }
 
 /// Critical message, not used yet - remove underscore when in use.
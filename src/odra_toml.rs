@@ -5,18 +5,44 @@ use std::path::{Path, PathBuf};
 use serde_derive::{Deserialize, Serialize};
 
 use crate::{
+    cargo_toml::load_cargo_toml,
     command,
     errors::{Error, Error::MalformedFqn},
+    paths::to_camel_case,
     project::Project,
+    sources::CrateSources,
 };
 
 /// Struct describing contract.
-#[derive(Deserialize, Serialize, Debug, Clone)]
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
 pub struct Contract {
     pub fqn: String,
+    /// Name of the wasm file, if it should differ from the struct name.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Cargo features enabled when building the contract.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub features: Vec<String>,
+    /// Human readable description of the contract.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Version of the contract, independent of the crate's version.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// Default arguments of the contract's constructor.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub init_args: Option<toml::Table>,
 }
 
 impl Contract {
+    /// Creates a contract with the given fqn and no metadata.
+    pub fn new(fqn: String) -> Self {
+        Contract {
+            fqn,
+            ..Default::default()
+        }
+    }
+
     /// Extracts first part from fqn
     pub fn module_name(&self) -> String {
         self.fqn
@@ -26,9 +52,11 @@ impl Contract {
             .to_string()
     }
 
+    /// Crate name of the contract. In a workspace it is the crate name of the member the fqn
+    /// starts with, which may be given by its path.
     pub fn crate_name(&self, project: &Project) -> String {
         if project.is_workspace() {
-            self.module_name()
+            project.crate_name(Some(self.module_name()))
         } else {
             project.project_crate_name()
         }
@@ -41,6 +69,99 @@ impl Contract {
             .unwrap_or_else(|| MalformedFqn.print_and_die())
             .to_string()
     }
+
+    /// Name of the contract's wasm file, the struct name unless `name` is set.
+    pub fn wasm_name(&self) -> String {
+        self.name.clone().unwrap_or_else(|| self.struct_name())
+    }
+
+    /// Checks if the contract is referred to by a name given on the command line,
+    /// either its struct name or its wasm name.
+    pub fn is_named(&self, name: &str) -> bool {
+        self.struct_name() == to_camel_case(name) || self.name.as_deref() == Some(name)
+    }
+
+    /// Cargo arguments selecting the contract's package and enabling its features.
+    /// Without the package, features can not be given in a virtual workspace, or are
+    /// enabled in every member having them.
+    pub fn cargo_args(&self, project: &Project) -> Vec<String> {
+        let mut args = vec!["--package".to_string(), self.package_name(project)];
+        if !self.features.is_empty() {
+            args.push("--features".to_string());
+            args.push(self.features.join(","));
+        }
+        args
+    }
+
+    /// Name of the package the contract is defined in, as set in its Cargo.toml.
+    pub fn package_name(&self, project: &Project) -> String {
+        load_cargo_toml(&self.crate_root(project).join("Cargo.toml"))
+            .package
+            .map(|package| package.name)
+            .unwrap_or_else(|| self.crate_name(project))
+    }
+
+    /// Roots of directories with wasm directories the contract is copied to during build.
+    pub fn wasm_roots(&self, project: &Project) -> Vec<PathBuf> {
+        let mut roots = vec![project.project_root()];
+        if project.is_workspace() {
+            roots.push(self.crate_root(project));
+        }
+        roots
+    }
+
+    /// Root directory of the crate the contract is defined in.
+    pub fn crate_root(&self, project: &Project) -> PathBuf {
+        if project.is_workspace() {
+            project.module_root(Some(self.module_name()))
+        } else {
+            project.project_root()
+        }
+    }
+
+    /// Path of modules leading to the contract struct, without the crate name.
+    pub fn module_path(&self, project: &Project) -> Vec<String> {
+        let mut segments: Vec<String> = self
+            .fqn
+            .split_terminator("::")
+            .map(ToString::to_string)
+            .collect();
+        segments.pop();
+        if project.is_workspace() && !segments.is_empty() {
+            segments.remove(0);
+        }
+        segments
+    }
+
+    /// Returns the file the contract struct is defined in, following re-exports.
+    pub fn source_file(&self, project: &Project) -> PathBuf {
+        let crate_root = self.crate_root(project);
+        let module_path = self.module_path(project);
+        // The struct may be re-exported or in a module with a custom path.
+        let scanned = CrateSources::scan(&crate_root).ok().and_then(|sources| {
+            sources
+                .find(&module_path, &self.struct_name())
+                .map(|module| module.file.clone())
+        });
+        if let Some(file) = scanned {
+            return file;
+        }
+
+        let src = crate_root.join("src");
+        if module_path.is_empty() {
+            return src.join("lib.rs");
+        }
+
+        let module_file = module_path
+            .iter()
+            .fold(src, |path, segment| path.join(segment));
+        let rs_file = module_file.with_extension("rs");
+        if rs_file.exists() {
+            rs_file
+        } else {
+            module_file.join("mod.rs")
+        }
+    }
 }
 
 /// Odra configuration.
@@ -57,7 +178,8 @@ impl OdraToml {
     pub fn load(location: &Path) -> OdraToml {
         let odra_conf = command::read_file_content(location.to_path_buf());
         let mut odra_toml: OdraToml = match odra_conf {
-            Ok(conf_file) => toml::from_str(conf_file.as_str()).unwrap(),
+            Ok(conf_file) => toml::from_str(conf_file.as_str())
+                .unwrap_or_else(|err| Self::parse_error(location, &conf_file, err).print_and_die()),
             Err(_) => Error::OdraTomlNotFound(location.to_path_buf()).print_and_die(),
         };
 
@@ -65,6 +187,20 @@ impl OdraToml {
         odra_toml
     }
 
+    /// Describes a parse error with the line and column it occurred at.
+    fn parse_error(location: &Path, content: &str, err: toml::de::Error) -> Error {
+        let position = err.span().map(|span| {
+            let before = &content[..span.start];
+            let line = before.matches('\n').count() + 1;
+            let column = before.len() - before.rfind('\n').map_or(0, |index| index + 1) + 1;
+            format!(" at line {line}, column {column}")
+        });
+        Error::FailedToParseOdraToml(
+            location.to_path_buf(),
+            format!("{}{}", err.message(), position.unwrap_or_default()),
+        )
+    }
+
     /// Saves configuration into Odra.toml file.
     pub fn save(&self) {
         let content = toml::to_string(&self).unwrap();
@@ -73,9 +209,7 @@ impl OdraToml {
 
     /// Check if the contract is defined in Odra.toml file.
     pub fn has_contract(&self, contract_name: &str) -> bool {
-        self.contracts
-            .iter()
-            .any(|c| c.struct_name() == contract_name)
+        self.contracts.iter().any(|c| c.is_named(contract_name))
     }
 
     /// Check if any contract in Odra.toml is a part of a module with given name
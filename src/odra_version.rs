@@ -0,0 +1,117 @@
+//! Module resolving versions of Odra.
+
+use std::{fs, path::PathBuf};
+
+use semver::Version;
+use ureq::serde_json::{self, Value};
+
+use crate::{
+    consts::{ODRA_INDEX_CACHE_GLOB, ODRA_SOURCES_CACHE_DIR},
+    errors::Error,
+    http,
+    log,
+    paths,
+};
+
+/// Returns the latest version of Odra. It is fetched from GitHub, then from the crates.io
+/// sparse index, and if that is not possible or `offline` is set, taken from the cached
+/// Odra sources or from the crates.io index on disk.
+pub fn latest(offline: bool) -> Result<String, Error> {
+    if !offline {
+        match latest_release() {
+            Ok(version) => return Ok(version),
+            Err(err) => log::warn(format!("{err} Trying the crates.io index.")),
+        }
+        match latest_published() {
+            Ok(version) => return Ok(version),
+            Err(err) => log::warn(format!("{err} Looking for a version available locally.")),
+        }
+    }
+
+    latest_local().ok_or_else(|| {
+        Error::FailedToResolveOdraVersion(
+            "no cached Odra sources or crates.io index data found.".to_string(),
+        )
+    })
+}
+
+/// Returns versions of Odra with cached sources.
+pub fn cached() -> Vec<Version> {
+    paths::cache_dir()
+        .join(ODRA_SOURCES_CACHE_DIR)
+        .read_dir()
+        .map(|entries| {
+            entries
+                .filter_map(Result::ok)
+                .filter_map(|entry| Version::parse(&entry.file_name().to_string_lossy()).ok())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Fetches the tag of the latest Odra release from GitHub.
+fn latest_release() -> Result<String, Error> {
+    let url = http::github_api_data();
+    let response = http::get_json(&url)?;
+    response["tag_name"]
+        .as_str()
+        .map(|tag| tag.trim_start_matches('v').to_string())
+        .ok_or_else(|| Error::FailedToResolveOdraVersion(format!("{url} has no tag_name.")))
+}
+
+/// Fetches the highest Odra version published on crates.io from its sparse index.
+fn latest_published() -> Result<String, Error> {
+    let url = http::crates_io_index();
+    let index = http::get_string(&url)?;
+    parse_index(index.as_bytes(), b'\n')
+        .into_iter()
+        .filter(|version| version.pre.is_empty())
+        .max()
+        .map(|version| version.to_string())
+        .ok_or_else(|| {
+            Error::FailedToResolveOdraVersion(format!("{url} has no released versions."))
+        })
+}
+
+/// Returns the highest version of Odra that is cached or present in the crates.io index on disk.
+fn latest_local() -> Option<String> {
+    let mut versions = cached();
+    versions.extend(indexed());
+    versions
+        .into_iter()
+        .filter(|version| version.pre.is_empty())
+        .max()
+        .map(|version| version.to_string())
+}
+
+/// Reads not yanked Odra versions from the cargo registry cache. The cache files hold
+/// json entries of the index, separated by zero bytes.
+fn indexed() -> Vec<Version> {
+    let Ok(cargo_home) = home::cargo_home() else {
+        return vec![];
+    };
+    let pattern = cargo_home.join(ODRA_INDEX_CACHE_GLOB);
+    let files: Vec<PathBuf> = glob::glob(&pattern.to_string_lossy())
+        .map(|paths| paths.filter_map(Result::ok).collect())
+        .unwrap_or_default();
+
+    files
+        .iter()
+        .filter_map(|file| fs::read(file).ok())
+        .flat_map(|content| parse_index(&content, 0))
+        .collect()
+}
+
+/// Parses not yanked versions from index entries separated by the given byte.
+fn parse_index(content: &[u8], separator: u8) -> Vec<Version> {
+    content
+        .split(|byte| *byte == separator)
+        .filter_map(|entry| serde_json::from_slice::<Value>(entry).ok())
+        .filter(|entry| entry["yanked"] != Value::Bool(true))
+        .filter_map(|entry| {
+            entry["vers"]
+                .as_str()
+                .and_then(|vers| Version::parse(vers).ok())
+        })
+        .collect()
+}
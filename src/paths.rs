@@ -1,67 +1,141 @@
 //! Paths utils.
 
 use std::{
-    path::{Path, PathBuf},
-    process::Command,
+    env,
+    fs,
+    iter,
+    path::{Component, Path, PathBuf},
 };
 
 use convert_case::{Boundary, Case, Casing};
 
+use crate::{
+    build_options::BuildOptions,
+    consts::{
+        CARGO_CONFIG_FILES,
+        CARGO_ODRA_CACHE_DIR,
+        CARGO_ODRA_CACHE_DIR_ENV_KEY,
+        CARGO_TARGET_DIR_ENV_KEYS,
+        ODRA_SOURCES_CACHE_DIR,
+        PROJECT_TEMPLATES_DIR,
+    },
+    log,
+};
+
+/// Returns the path relative to the base directory. If either of them does not exist,
+/// the path is returned as it is.
+pub fn relative_path(path: &Path, base: &Path) -> PathBuf {
+    let (Ok(path), Ok(base)) = (path.canonicalize(), base.canonicalize()) else {
+        return path.to_path_buf();
+    };
+    let common = path
+        .components()
+        .zip(base.components())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let relative: PathBuf = iter::repeat(Component::ParentDir)
+        .take(base.components().count() - common)
+        .chain(path.components().skip(common))
+        .collect();
+    match relative.as_os_str().is_empty() {
+        true => PathBuf::from("."),
+        false => relative,
+    }
+}
+
 /// Returns *.wasm filename.
 pub fn wasm_file_name(contract_name: &str) -> PathBuf {
     PathBuf::from(contract_name).with_extension("wasm")
 }
 
 /// Returns *.wasm file path in target directory.
-pub fn wasm_path_in_target(contract_name: &str, project_root: PathBuf) -> PathBuf {
-    // extract target dir
-    let target_dir = get_build_target_dir();
-    project_root
-        .join(format!(
-            "{}/wasm32-unknown-unknown/release",
-            target_dir.display()
-        ))
+pub fn wasm_path_in_target(
+    contract_name: &str,
+    project_root: PathBuf,
+    options: &BuildOptions,
+) -> PathBuf {
+    let target_dir = options
+        .target_dir()
+        .map(|target_dir| project_root.join(target_dir))
+        .unwrap_or_else(|| build_target_dir(&project_root));
+    target_dir
+        .join("wasm32-unknown-unknown")
+        .join(options.profile_dir())
         .join(wasm_file_name(contract_name))
 }
 
-fn get_build_target_dir() -> PathBuf {
-    let args: Vec<String> = "config get build.target-dir -Z unstable-options"
-        .to_string()
-        .split(' ')
-        .map(|s| s.to_string())
-        .collect();
-    let output = Command::new("cargo").args(args).output();
-
-    if output.is_err() {
-        return PathBuf::from("target");
-    }
-
-    // convert output to string
-    let output_string = String::from_utf8(output.unwrap().stdout);
+/// Resolves the target directory the way cargo does: from the env, then from the config
+/// files from the project root up, then from `$CARGO_HOME`. Defaults to `target`.
+pub fn build_target_dir(project_root: &Path) -> PathBuf {
+    let target_dir = CARGO_TARGET_DIR_ENV_KEYS
+        .iter()
+        .find_map(|key| env::var_os(key).filter(|value| !value.is_empty()))
+        .map(|target_dir| project_root.join(target_dir))
+        .or_else(|| config_target_dir(project_root))
+        .unwrap_or_else(|| project_root.join("target"));
+    log::verbose(format!("Target directory: {}", target_dir.display()));
+    target_dir
+}
 
-    if output_string.is_err() {
-        return PathBuf::from("target");
+/// Finds `build.target-dir` in the cargo config files. A relative path is resolved against
+/// the directory containing the `.cargo` directory.
+fn config_target_dir(project_root: &Path) -> Option<PathBuf> {
+    let mut config_dirs: Vec<PathBuf> = project_root
+        .ancestors()
+        .map(|dir| dir.join(".cargo"))
+        .collect();
+    if let Ok(cargo_home) = home::cargo_home() {
+        if !config_dirs.contains(&cargo_home) {
+            config_dirs.push(cargo_home);
+        }
     }
 
-    let output_string = output_string.unwrap();
+    config_dirs.iter().find_map(|config_dir| {
+        let config_file = CARGO_CONFIG_FILES
+            .iter()
+            .map(|file| config_dir.join(file))
+            .find(|path| path.exists())?;
+        let content = fs::read_to_string(config_file).ok()?;
+        let config: toml::Table = toml::from_str(&content).ok()?;
+        let target_dir = config.get("build")?.get("target-dir")?.as_str()?;
+        let base = config_dir.parent().unwrap_or(config_dir);
+        Some(base.join(target_dir))
+    })
+}
 
-    // output is in format build.target-dir = "../target"
-    // convert it to PathBuf
-    let target_dir = output_string.split('=').collect::<Vec<&str>>();
-    let target_dir = target_dir.get(1);
+/// Returns *.wasm file path in wasm directory.
+pub fn wasm_path_in_wasm_dir(contract_name: &str, project_root: &Path) -> PathBuf {
+    wasm_dir(project_root).join(wasm_file_name(contract_name))
+}
 
-    if target_dir.is_none() {
-        return PathBuf::from("target");
-    }
+/// Returns path of the file describing a reproducible build of a contract.
+pub fn build_info_path(contract_name: &str, project_root: &Path) -> PathBuf {
+    wasm_dir(project_root).join(format!("{contract_name}.build.json"))
+}
 
-    let target = target_dir.unwrap().trim().to_string().replace('\"', "");
+/// Returns path of the source verification bundle of a contract.
+pub fn verification_bundle_path(contract_name: &str, project_root: &Path) -> PathBuf {
+    wasm_dir(project_root).join(format!("{contract_name}.bundle.tar.gz"))
+}
 
-    PathBuf::from(target)
+/// Returns paths of all files generated for a contract in a wasm directory.
+pub fn wasm_artifacts(contract_name: &str, project_root: &Path) -> Vec<PathBuf> {
+    vec![
+        wasm_path_in_wasm_dir(contract_name, project_root),
+        build_info_path(contract_name, project_root),
+        verification_bundle_path(contract_name, project_root),
+    ]
 }
 
-/// Returns *.wasm file path in wasm directory.
-pub fn wasm_path_in_wasm_dir(contract_name: &str, project_root: &Path) -> PathBuf {
-    wasm_dir(project_root).join(wasm_file_name(contract_name))
+/// Returns paths of schema files Odra generates for a contract.
+pub fn schema_paths(contract_name: &str, project_root: &Path) -> Vec<PathBuf> {
+    let file_name = format!("{}_schema.json", to_snake_case(contract_name));
+    let resources = project_root.join("resources");
+    vec![
+        resources.join(&file_name),
+        resources.join("casper_contract_schemas").join(&file_name),
+        resources.join("legacy").join(&file_name),
+    ]
 }
 
 /// Returns wasm directory path.
@@ -69,6 +143,41 @@ pub fn wasm_dir(project_root: &Path) -> PathBuf {
     project_root.join("wasm")
 }
 
+/// Returns cargo-odra cache directory.
+pub fn cache_dir() -> PathBuf {
+    match env::var(CARGO_ODRA_CACHE_DIR_ENV_KEY) {
+        Ok(dir) => PathBuf::from(dir),
+        Err(_) => dirs::cache_dir()
+            .unwrap_or_else(env::temp_dir)
+            .join(CARGO_ODRA_CACHE_DIR),
+    }
+}
+
+/// Returns path of a cached code template fetched from a given git reference of Odra.
+pub fn template_cache_path(git_ref: &str, template_name: &str) -> PathBuf {
+    cache_dir()
+        .join("templates")
+        .join(git_ref)
+        .join(template_file_name(template_name))
+}
+
+/// Returns path of the cached Odra source tree of a given version.
+pub fn odra_source_cache_path(version: &str) -> PathBuf {
+    cache_dir().join(ODRA_SOURCES_CACHE_DIR).join(version)
+}
+
+/// Returns path of a project-level code template.
+pub fn project_template_path(template_name: &str, project_root: &Path) -> PathBuf {
+    project_root
+        .join(PROJECT_TEMPLATES_DIR)
+        .join(template_file_name(template_name))
+}
+
+/// Returns *.rs.template filename.
+pub fn template_file_name(template_name: &str) -> PathBuf {
+    PathBuf::from(template_name).with_extension("rs.template")
+}
+
 /// Convert text to a sneak case.
 pub fn to_snake_case<T: AsRef<str>>(text: T) -> String {
     text.as_ref()
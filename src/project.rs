@@ -1,9 +1,10 @@
 use std::{
     env,
+    fmt::{self, Display, Formatter},
     path::{Path, PathBuf},
 };
 
-use cargo_toml::{Dependency, DependencyDetail};
+use cargo_toml::{Dependency, DependencyDetail, Manifest};
 
 use crate::{cargo_toml::load_cargo_toml, errors::Error, odra_toml::OdraToml};
 
@@ -61,7 +62,7 @@ impl Project {
             Some(module_name) => self
                 .members
                 .iter()
-                .find(|member| member.name == module_name)
+                .find(|member| member.is_named(&module_name))
                 .unwrap_or_else(|| {
                     Error::ModuleNotFound(module_name).print_and_die();
                 })
@@ -83,11 +84,11 @@ impl Project {
                 let found_member = self
                     .members
                     .iter()
-                    .find(|member| member.name == module_name);
+                    .find(|member| member.is_named(&module_name));
 
                 match found_member {
                     None => self.project_crate_name(),
-                    Some(member) => member.name.clone().replace('-', "_"),
+                    Some(member) => member.name.clone(),
                 }
             }
         }
@@ -113,6 +114,13 @@ impl Project {
         }
     }
 
+    /// Searches for the root of a project, which may not have Odra.toml yet.
+    pub fn find_project_root(path: PathBuf) -> Option<PathBuf> {
+        Self::find_odra_toml(path.clone())
+            .or_else(|| Self::find_file_upwards("Cargo.toml", path))
+            .and_then(|file| file.parent().map(Path::to_path_buf))
+    }
+
     /// Root directory of the Project.
     pub fn project_root(&self) -> PathBuf {
         self.project_root.clone()
@@ -126,11 +134,12 @@ impl Project {
     pub fn members(cargo_toml_path: &PathBuf, odra_toml_path: &Path) -> Vec<Member> {
         Self::detect_members(cargo_toml_path, odra_toml_path)
             .iter()
-            .map(|member| {
-                let root = cargo_toml_path.parent().unwrap().join(member.clone().1);
+            .map(|(name, path)| {
+                let root = cargo_toml_path.parent().unwrap().join(path);
                 let cargo_toml = root.join("Cargo.toml");
                 Member {
-                    name: member.clone().0,
+                    name: name.clone(),
+                    path: path.clone(),
                     root,
                     cargo_toml,
                 }
@@ -156,19 +165,45 @@ impl Project {
         }
     }
 
+    /// Returns crate names and paths of the Odra crates of the workspace.
     fn detect_members(cargo_toml_path: &PathBuf, odra_toml_path: &Path) -> Vec<(String, String)> {
         let odra_toml = OdraToml::load(odra_toml_path);
         match load_cargo_toml(cargo_toml_path).workspace {
             Some(workspace) => workspace
                 .members
                 .iter()
-                .filter(|member| odra_toml.has_module(member))
-                .map(|member| (member.clone(), member.clone()))
+                .map(|path| {
+                    let root = cargo_toml_path.parent().unwrap().join(path);
+                    (Self::member_crate_name(&root, path), path.clone())
+                })
+                .filter(|(name, path)| {
+                    odra_toml.has_module(name)
+                        || odra_toml.has_module(path)
+                        || Self::depends_on_odra(&cargo_toml_path.parent().unwrap().join(path))
+                })
                 .collect(),
             None => vec![],
         }
     }
 
+    /// Returns the crate name of a workspace member, taken from its package name.
+    /// Falls back to the member's path if its Cargo.toml can not be read.
+    pub fn member_crate_name(member_root: &Path, path: &str) -> String {
+        Manifest::from_path(member_root.join("Cargo.toml"))
+            .ok()
+            .and_then(|manifest| manifest.package)
+            .map(|package| package.name)
+            .unwrap_or_else(|| path.to_string())
+            .replace('-', "_")
+    }
+
+    /// Checks if the crate in the given directory has Odra as a dependency.
+    fn depends_on_odra(crate_root: &Path) -> bool {
+        Manifest::from_path(crate_root.join("Cargo.toml"))
+            .map(|manifest| manifest.dependencies.contains_key("odra"))
+            .unwrap_or(false)
+    }
+
     pub fn project_odra_location(&self) -> OdraLocation {
         let cargo_toml = load_cargo_toml(&self.cargo_toml_location);
         let dependencies = match cargo_toml.workspace {
@@ -199,14 +234,23 @@ impl Project {
             }
             Dependency::Detailed(DependencyDetail {
                 git: Some(git),
-                branch: Some(branch),
-                ..
-            }) => OdraLocation::Remote(git, Some(branch)),
-            Dependency::Detailed(DependencyDetail {
-                git: Some(git),
-                branch: None,
+                branch,
+                tag,
+                rev,
                 ..
-            }) => OdraLocation::Remote(git, None),
+            }) => {
+                let reference = match (branch, tag, rev) {
+                    (Some(branch), None, None) => Some(GitReference::Branch(branch)),
+                    (None, Some(tag), None) => Some(GitReference::Tag(tag)),
+                    (None, None, Some(rev)) => Some(GitReference::Rev(rev)),
+                    (None, None, None) => None,
+                    _ => Error::FailedToReadCargo(
+                        "Odra dependency has more than one of branch, tag and rev.".to_string(),
+                    )
+                    .print_and_die(),
+                };
+                OdraLocation::Remote(git, reference)
+            }
             _ => {
                 Error::FailedToReadCargo("Unsupported location of Odra.".to_string())
                     .print_and_die();
@@ -218,17 +262,82 @@ impl Project {
 #[derive(Debug, Clone)]
 pub enum OdraLocation {
     Local(PathBuf),
-    /// git repo, branch
-    Remote(String, Option<String>),
+    /// git repo, reference
+    Remote(String, Option<GitReference>),
     CratesIO(String),
 }
 
+impl Display for OdraLocation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            OdraLocation::Local(path) => write!(f, "{}", path.display()),
+            OdraLocation::Remote(repository, Some(reference)) => {
+                write!(f, "{repository} at {reference}")
+            }
+            OdraLocation::Remote(repository, None) => write!(f, "{repository}"),
+            OdraLocation::CratesIO(version) => write!(f, "{version}"),
+        }
+    }
+}
+
+/// Reference of a git repository.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GitReference {
+    Branch(String),
+    Tag(String),
+    Rev(String),
+}
+
+impl GitReference {
+    /// Parses `branch:name`, `tag:name` or `rev:sha`. Without a prefix, it is a branch.
+    pub fn parse(reference: &str) -> Self {
+        match reference.split_once(':') {
+            Some(("tag", tag)) => GitReference::Tag(tag.to_string()),
+            Some(("rev", rev)) => GitReference::Rev(rev.to_string()),
+            Some(("branch", branch)) => GitReference::Branch(branch.to_string()),
+            _ => GitReference::Branch(reference.to_string()),
+        }
+    }
+
+    /// Name of the branch or tag, or the revision.
+    pub fn name(&self) -> &str {
+        match self {
+            GitReference::Branch(name) | GitReference::Tag(name) | GitReference::Rev(name) => name,
+        }
+    }
+
+    /// Tags and revisions point to the same commit forever, branches move.
+    pub fn is_immutable(&self) -> bool {
+        !matches!(self, GitReference::Branch(_))
+    }
+}
+
+impl Display for GitReference {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            GitReference::Branch(branch) => write!(f, "branch {branch}"),
+            GitReference::Tag(tag) => write!(f, "tag {tag}"),
+            GitReference::Rev(rev) => write!(f, "rev {rev}"),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Member {
-    /// Name of the member.
+    /// Crate name of the member, the first segment of its contracts' fqns.
     pub name: String,
+    /// Path of the member in `workspace.members`.
+    pub path: String,
     /// Root directory of the member.
     pub root: PathBuf,
     /// Path to the Cargo.toml file.
     pub cargo_toml: PathBuf,
 }
+
+impl Member {
+    /// Checks if the member is referred to by the name, either its crate name or its path
+    /// in the workspace.
+    pub fn is_named(&self, name: &str) -> bool {
+        self.name == name.replace('-', "_") || self.path == name
+    }
+}
@@ -0,0 +1,173 @@
+//! Module containing helpers for reproducible, verifiable builds.
+
+use std::{
+    env,
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use sha2::{Digest, Sha256};
+use ureq::serde_json::{self, json};
+
+use crate::{
+    command,
+    consts::{
+        REPRODUCIBLE_CARGO_HOME_PREFIX,
+        REPRODUCIBLE_PROJECT_PREFIX,
+        RUSTUP_TOOLCHAIN_ENV_KEY,
+        RUST_TOOLCHAIN_FILES,
+        SOURCE_DATE_EPOCH_ENV_KEY,
+    },
+    errors::Error,
+    log,
+    odra_toml::Contract,
+    paths,
+};
+
+/// Returns the toolchain pinned by the project's toolchain file.
+pub fn toolchain(project_root: &Path) -> String {
+    let path = toolchain_file(project_root).unwrap_or_else(|| {
+        Error::ToolchainFileNotFound(project_root.to_path_buf()).print_and_die()
+    });
+    command::read_file_content(path.clone())
+        .ok()
+        .and_then(|content| parse_toolchain(&content))
+        .unwrap_or_else(|| Error::MalformedToolchainFile(path).print_and_die())
+}
+
+/// Returns path of the project's toolchain file, if there is one.
+pub fn toolchain_file(project_root: &Path) -> Option<PathBuf> {
+    RUST_TOOLCHAIN_FILES
+        .iter()
+        .map(|file| project_root.join(file))
+        .find(|path| path.exists())
+}
+
+/// Sets up the environment, so every following cargo invocation is reproducible.
+pub fn prepare_environment(project_root: &Path) {
+    let toolchain = toolchain(project_root);
+    log::info(format!("Pinning toolchain to {toolchain}..."));
+    env::set_var(RUSTUP_TOOLCHAIN_ENV_KEY, toolchain);
+
+    if env::var(SOURCE_DATE_EPOCH_ENV_KEY).is_err() {
+        env::set_var(SOURCE_DATE_EPOCH_ENV_KEY, "0");
+    }
+
+    if env::var("RUSTFLAGS").is_ok() {
+        log::warn("RUSTFLAGS are ignored in reproducible builds.");
+        env::remove_var("RUSTFLAGS");
+    }
+}
+
+/// Returns cargo arguments that make the wasm build independent of the machine it runs on.
+pub fn cargo_args(project_root: &Path) -> Vec<String> {
+    let cargo_home = home::cargo_home()
+        .ok()
+        .map(|cargo_home| cargo_home.display().to_string());
+    remapping_cargo_args(&project_root.display().to_string(), cargo_home, None)
+}
+
+/// Returns [cargo_args] with the machine specific paths replaced by `$PROJECT_ROOT`
+/// and `$CARGO_HOME` placeholders. Crates vendored into `$PROJECT_ROOT/vendor` are
+/// remapped to the registry sources directory they were built from, e.g.
+/// `registry/src/index.crates.io-6f17d22bba15001f`, so the wasm stays the same.
+pub fn portable_cargo_args(registry_src: Option<&str>) -> Vec<String> {
+    let vendor = registry_src.map(|registry_src| {
+        remap_path_prefix(
+            "$PROJECT_ROOT/vendor",
+            &format!("{REPRODUCIBLE_CARGO_HOME_PREFIX}/{registry_src}"),
+        )
+    });
+    remapping_cargo_args("$PROJECT_ROOT", Some("$CARGO_HOME".to_string()), vendor)
+}
+
+/// Writes the hash of the contract's wasm and the versions of tools used to build it.
+pub fn record_build_info(contract: &Contract, project_root: &Path) {
+    let contract_name = contract.wasm_name();
+    let path = paths::build_info_path(&contract_name, project_root);
+    if command::is_dry_run() {
+        log::info(format!("Would save {}", path.display()));
+        return;
+    }
+
+    let wasm_path = paths::wasm_path_in_wasm_dir(&contract_name, project_root);
+    let build_info = json!({
+        "contract": contract_name,
+        "fqn": contract.fqn,
+        "version": contract.version,
+        "features": contract.features,
+        "sha256": sha256(&wasm_path),
+        "toolchain": toolchain(project_root),
+        "source_date_epoch": env::var(SOURCE_DATE_EPOCH_ENV_KEY).unwrap_or_default(),
+        "tools": {
+            "cargo-odra": env!("CARGO_PKG_VERSION"),
+            "cargo": tool_version("cargo"),
+            "rustc": tool_version("rustc"),
+            "wasm-opt": tool_version("wasm-opt"),
+            "wasm-strip": tool_version("wasm-strip"),
+        },
+    });
+
+    log::info(format!("Saving {}", path.display()));
+    command::write_to_file(path, &serde_json::to_string_pretty(&build_info).unwrap());
+}
+
+/// Returns hex encoded sha256 hash of a wasm file.
+pub fn sha256(path: &Path) -> String {
+    let bytes = fs::read(path).unwrap_or_else(|_| {
+        Error::WasmFileNotFound(path.to_path_buf()).print_and_die();
+    });
+    hex::encode(Sha256::digest(bytes))
+}
+
+/// Reads the toolchain either from the legacy one-line format or from the `[toolchain]` table.
+fn parse_toolchain(content: &str) -> Option<String> {
+    match toml::from_str::<toml::Table>(content) {
+        Ok(table) => table
+            .get("toolchain")?
+            .get("channel")?
+            .as_str()
+            .map(ToString::to_string),
+        Err(_) => content
+            .lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty())
+            .map(ToString::to_string),
+    }
+}
+
+/// The last matching remap wins, so the more specific ones go last.
+fn remapping_cargo_args(
+    project_root: &str,
+    cargo_home: Option<String>,
+    extra_remap: Option<String>,
+) -> Vec<String> {
+    let mut remaps = vec![remap_path_prefix(project_root, REPRODUCIBLE_PROJECT_PREFIX)];
+    if let Some(cargo_home) = cargo_home {
+        remaps.push(remap_path_prefix(
+            &cargo_home,
+            REPRODUCIBLE_CARGO_HOME_PREFIX,
+        ));
+    }
+    remaps.extend(extra_remap);
+
+    let rustflags = toml::Value::Array(remaps.into_iter().map(toml::Value::String).collect());
+    vec![
+        "--locked".to_string(),
+        "--config".to_string(),
+        format!("target.wasm32-unknown-unknown.rustflags={rustflags}"),
+    ]
+}
+
+fn remap_path_prefix(from: &str, to: &str) -> String {
+    format!("--remap-path-prefix={}={}", from, to)
+}
+
+fn tool_version(program: &str) -> String {
+    command::output(Command::new(program).arg("--version"))
+        .ok()
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|version| version.trim().to_string())
+        .unwrap_or_else(|| "unknown".to_string())
+}
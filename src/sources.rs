@@ -0,0 +1,377 @@
+//! Module scanning crate sources for Odra modules.
+
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+};
+
+use syn::{
+    Attribute,
+    Expr,
+    GenericArgument,
+    Item,
+    Lit,
+    Meta,
+    PathArguments,
+    Type,
+    UseTree,
+    Visibility,
+};
+
+use crate::{command, errors::Error};
+
+/// Struct annotated with `#[odra::module]`.
+#[derive(Debug, Clone)]
+pub struct ModuleStruct {
+    /// Path of modules leading to the struct, without the crate name.
+    pub module_path: Vec<String>,
+    /// Name of the struct.
+    pub struct_name: String,
+    /// File the struct is defined in.
+    pub file: PathBuf,
+}
+
+/// Odra modules defined in a crate.
+#[derive(Debug, Default)]
+pub struct CrateSources {
+    /// All structs annotated with `#[odra::module]`.
+    pub modules: Vec<ModuleStruct>,
+    /// Names of types used as `SubModule<T>` fields.
+    submodules: HashSet<String>,
+    /// Items re-exported with `pub use`.
+    reexports: Vec<Reexport>,
+}
+
+/// Item re-exported with `pub use`.
+#[derive(Debug, Clone)]
+struct Reexport {
+    /// Path of the module containing the `use` item.
+    module_path: Vec<String>,
+    /// Path of the re-exported item, as written.
+    target: Vec<String>,
+    /// Name the item is exported under, `None` for a glob.
+    name: Option<String>,
+}
+
+/// Limit of re-exports followed when resolving a path, guarding against cycles.
+const MAX_REEXPORT_DEPTH: usize = 16;
+
+impl CrateSources {
+    /// Scans all modules reachable from src/lib.rs of a crate, skipping `#[cfg(test)]` ones.
+    pub fn scan(crate_root: &Path) -> Result<CrateSources, Error> {
+        let mut sources = CrateSources::default();
+        let lib_rs = crate_root.join("src").join("lib.rs");
+        sources.scan_file(&lib_rs, &crate_root.join("src"), vec![])?;
+        Ok(sources)
+    }
+
+    /// Returns modules which are meant to be deployed - the ones that are not used
+    /// as a submodule of another module.
+    pub fn deployable(&self) -> impl Iterator<Item = &ModuleStruct> {
+        self.modules
+            .iter()
+            .filter(|module| !self.submodules.contains(&module.struct_name))
+    }
+
+    /// Checks if a module struct is defined at the given path, or re-exported to it with
+    /// `pub use`.
+    pub fn contains(&self, module_path: &[String], struct_name: &str) -> bool {
+        self.find(module_path, struct_name).is_some()
+    }
+
+    /// Finds a module struct defined at the given path, or re-exported to it with `pub use`.
+    pub fn find(&self, module_path: &[String], struct_name: &str) -> Option<&ModuleStruct> {
+        self.resolve(module_path, struct_name, 0)
+    }
+
+    fn resolve(&self, module_path: &[String], name: &str, depth: usize) -> Option<&ModuleStruct> {
+        if depth > MAX_REEXPORT_DEPTH {
+            return None;
+        }
+        let defined = self
+            .modules
+            .iter()
+            .find(|module| module.module_path == module_path && module.struct_name == name);
+        if defined.is_some() {
+            return defined;
+        }
+
+        // The struct itself is re-exported.
+        let reexported = self
+            .reexports_in(module_path)
+            .find_map(|reexport| match &reexport.name {
+                Some(exported) if exported == name => {
+                    reexport.targets().iter().find_map(|target| {
+                        let (leaf, module) = target.split_last()?;
+                        self.resolve(module, leaf, depth + 1)
+                    })
+                }
+                Some(_) => None,
+                None => reexport
+                    .targets()
+                    .iter()
+                    .find_map(|module| self.resolve(module, name, depth + 1)),
+            });
+        if reexported.is_some() {
+            return reexported;
+        }
+
+        // The module containing the struct is re-exported.
+        let (module_name, parent) = module_path.split_last()?;
+        self.reexports_in(parent).find_map(|reexport| {
+            let modules = match &reexport.name {
+                Some(exported) if exported == module_name => reexport.targets(),
+                Some(_) => vec![],
+                None => reexport
+                    .targets()
+                    .into_iter()
+                    .map(|mut module| {
+                        module.push(module_name.clone());
+                        module
+                    })
+                    .collect(),
+            };
+            modules
+                .iter()
+                .find_map(|module| self.resolve(module, name, depth + 1))
+        })
+    }
+
+    fn reexports_in<'a>(&'a self, module_path: &'a [String]) -> impl Iterator<Item = &'a Reexport> {
+        self.reexports
+            .iter()
+            .filter(move |reexport| reexport.module_path == module_path)
+    }
+
+    fn scan_file(
+        &mut self,
+        file: &Path,
+        dir: &Path,
+        module_path: Vec<String>,
+    ) -> Result<(), Error> {
+        let content = command::read_file_content(file.to_path_buf())
+            .map_err(|_| Error::ModuleNotFound(module_path.join("::")))?;
+        let parsed = syn::parse_file(&content).map_err(|err| {
+            let start = err.span().start();
+            Error::FailedToParseRustFile(
+                file.to_path_buf(),
+                format!(
+                    "{} at line {}, column {}",
+                    err,
+                    start.line,
+                    start.column + 1
+                ),
+            )
+        })?;
+        // Outside of inline modules, `#[path]` is relative to the directory of the file.
+        let path_dir = file.parent().unwrap_or(Path::new(""));
+        self.scan_items(&parsed.items, file, dir, path_dir, module_path)
+    }
+
+    /// Scans the items of a module. Submodule files are looked for in `dir`, and paths set
+    /// by `#[path]` are resolved against `path_dir`.
+    fn scan_items(
+        &mut self,
+        items: &[Item],
+        file: &Path,
+        dir: &Path,
+        path_dir: &Path,
+        module_path: Vec<String>,
+    ) -> Result<(), Error> {
+        for item in items {
+            match item {
+                Item::Struct(item_struct) => {
+                    if item_struct.attrs.iter().any(is_odra_module) {
+                        self.modules.push(ModuleStruct {
+                            module_path: module_path.clone(),
+                            struct_name: item_struct.ident.to_string(),
+                            file: file.to_path_buf(),
+                        });
+                    }
+                    item_struct
+                        .fields
+                        .iter()
+                        .filter_map(|field| submodule_type(&field.ty))
+                        .for_each(|submodule| {
+                            self.submodules.insert(submodule);
+                        });
+                }
+                Item::Mod(item_mod) if !item_mod.attrs.iter().any(is_cfg_test) => {
+                    let name = item_mod.ident.to_string();
+                    let mut inner_path = module_path.clone();
+                    inner_path.push(name.clone());
+                    match &item_mod.content {
+                        Some((_, items)) => {
+                            let inner_dir = dir.join(&name);
+                            self.scan_items(items, file, &inner_dir, &inner_dir, inner_path)?
+                        }
+                        None => {
+                            let (module_file, module_dir) =
+                                module_file(&item_mod.attrs, &name, dir, path_dir);
+                            self.scan_file(&module_file, &module_dir, inner_path)?
+                        }
+                    }
+                }
+                Item::Use(item_use) if !matches!(item_use.vis, Visibility::Inherited) => {
+                    let mut paths = vec![];
+                    use_paths(&item_use.tree, vec![], &mut paths);
+                    self.reexports
+                        .extend(paths.into_iter().map(|(target, name)| Reexport {
+                            module_path: module_path.clone(),
+                            target,
+                            name,
+                        }));
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Reexport {
+    /// Returns the paths, relative to the crate root, the target may point to. Paths
+    /// without `crate`, `self` or `super` may be relative to the module or to the root.
+    fn targets(&self) -> Vec<Vec<String>> {
+        let Some(first) = self.target.first() else {
+            return vec![];
+        };
+        match first.as_str() {
+            "crate" => vec![self.target[1..].to_vec()],
+            "self" | "super" => {
+                let mut path = self.module_path.clone();
+                let mut rest = self.target.as_slice();
+                while let Some((segment, tail)) = rest.split_first() {
+                    match segment.as_str() {
+                        "self" => {}
+                        "super" if path.pop().is_none() => return vec![],
+                        "super" => {}
+                        _ => break,
+                    }
+                    rest = tail;
+                }
+                path.extend_from_slice(rest);
+                vec![path]
+            }
+            _ => {
+                let mut relative = self.module_path.clone();
+                relative.extend_from_slice(&self.target);
+                vec![relative, self.target.clone()]
+            }
+        }
+    }
+}
+
+/// Flattens a use tree into the paths it imports and the names they are imported as.
+fn use_paths(tree: &UseTree, prefix: Vec<String>, paths: &mut Vec<(Vec<String>, Option<String>)>) {
+    match tree {
+        UseTree::Path(path) => {
+            let mut prefix = prefix;
+            prefix.push(path.ident.to_string());
+            use_paths(&path.tree, prefix, paths);
+        }
+        UseTree::Name(name) if name.ident == "self" => {
+            let name = prefix.last().cloned();
+            paths.push((prefix, name));
+        }
+        UseTree::Name(name) => {
+            let mut target = prefix;
+            target.push(name.ident.to_string());
+            paths.push((target, Some(name.ident.to_string())));
+        }
+        UseTree::Rename(rename) => {
+            let mut target = prefix;
+            if rename.ident != "self" {
+                target.push(rename.ident.to_string());
+            }
+            paths.push((target, Some(rename.rename.to_string())));
+        }
+        UseTree::Glob(_) => paths.push((prefix, None)),
+        UseTree::Group(group) => group
+            .items
+            .iter()
+            .for_each(|tree| use_paths(tree, prefix.clone(), paths)),
+    }
+}
+
+/// Checks if an attribute is `#[odra::module]`, with or without arguments.
+pub fn is_odra_module(attr: &Attribute) -> bool {
+    let segments: Vec<String> = attr
+        .path()
+        .segments
+        .iter()
+        .map(|segment| segment.ident.to_string())
+        .collect();
+    segments == ["odra", "module"]
+}
+
+/// Checks if an attribute is `#[cfg(test)]`.
+fn is_cfg_test(attr: &Attribute) -> bool {
+    match &attr.meta {
+        Meta::List(list) => list.path.is_ident("cfg") && list.tokens.to_string() == "test",
+        _ => false,
+    }
+}
+
+/// Returns `T` if the type is `SubModule<T>`.
+fn submodule_type(ty: &Type) -> Option<String> {
+    let Type::Path(type_path) = ty else {
+        return None;
+    };
+    let segment = type_path.path.segments.last()?;
+    if segment.ident != "SubModule" {
+        return None;
+    }
+    let PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
+    };
+    match args.args.first()? {
+        GenericArgument::Type(Type::Path(inner)) => inner
+            .path
+            .segments
+            .last()
+            .map(|segment| segment.ident.to_string()),
+        _ => None,
+    }
+}
+
+/// Finds the file of a `mod name;` declaration, honoring the `#[path]` attribute, and the
+/// directory of its submodules. A file set by `#[path]` keeps its submodules next to it,
+/// like `mod.rs`.
+fn module_file(attrs: &[Attribute], name: &str, dir: &Path, path_dir: &Path) -> (PathBuf, PathBuf) {
+    let path_attr = attrs.iter().find_map(|attr| match &attr.meta {
+        Meta::NameValue(name_value) if name_value.path.is_ident("path") => {
+            match &name_value.value {
+                Expr::Lit(expr) => match &expr.lit {
+                    Lit::Str(path) => Some(path.value()),
+                    _ => None,
+                },
+                _ => None,
+            }
+        }
+        _ => None,
+    });
+    if let Some(path) = path_attr {
+        let file = path_dir.join(path);
+        let file_dir = file.parent().unwrap_or(Path::new("")).to_path_buf();
+        return (file, file_dir);
+    }
+
+    let file = dir.join(name).with_extension("rs");
+    let file = if file.exists() {
+        file
+    } else {
+        dir.join(name).join("mod.rs")
+    };
+    let file_dir = module_dir(&file);
+    (file, file_dir)
+}
+
+/// Returns the directory in which submodules of a module file are placed.
+fn module_dir(module_file: &Path) -> PathBuf {
+    let parent = module_file.parent().unwrap_or(Path::new("")).to_path_buf();
+    match module_file.file_name().and_then(|name| name.to_str()) {
+        Some("mod.rs") | Some("lib.rs") => parent,
+        _ => parent.join(module_file.file_stem().unwrap_or_default()),
+    }
+}
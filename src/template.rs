@@ -1,9 +1,20 @@
-use ureq::get;
+use std::path::PathBuf;
 
 use crate::{
-    command::read_file_content,
-    consts::{MODULE_REGISTER, MODULE_TEMPLATE},
+    command::{self, read_file_content},
+    consts::{
+        BUILT_IN_TEMPLATES,
+        ERROR_TEMPLATE,
+        EVENT_TEMPLATE,
+        MODULE_REGISTER,
+        MODULE_TEMPLATE,
+        SUBMODULE_TEMPLATE,
+        TEST_TEMPLATE,
+    },
     errors::Error,
+    http,
+    log,
+    paths,
     project::OdraLocation,
 };
 
@@ -11,58 +22,137 @@ use crate::{
 pub struct TemplateGenerator {
     raw_repository_path: String,
     odra_location: OdraLocation,
+    project_root: PathBuf,
+    offline: bool,
 }
 
 impl TemplateGenerator {
-    pub fn new(repository_path: String, odra_location: OdraLocation) -> Self {
+    pub fn new(
+        repository_path: String,
+        odra_location: OdraLocation,
+        project_root: PathBuf,
+        offline: bool,
+    ) -> Self {
         Self {
             raw_repository_path: repository_path,
             odra_location,
+            project_root,
+            offline,
         }
     }
 
-    fn template_path(&self, template_name: &str, branch: String) -> String {
+    fn template_path(&self, template_name: &str, branch: &str) -> String {
         format!(
             "{}/{}/templates/{}.rs.template",
             self.raw_repository_path, branch, template_name
         )
     }
 
+    /// Fetches a template, falling back to the built-in one if the Odra source does not
+    /// have it, as older versions do not ship all templates.
     fn fetch_template(&self, template_name: &str) -> Result<String, Error> {
+        let built_in = BUILT_IN_TEMPLATES
+            .iter()
+            .find(|(name, _)| *name == template_name)
+            .map(|(_, template)| template.to_string());
+        match (self.fetch_odra_template(template_name), built_in) {
+            (Err(err), Some(built_in)) => {
+                log::verbose(format!("{err} Using the built-in template."));
+                Ok(built_in)
+            }
+            (result, _) => result,
+        }
+    }
+
+    fn fetch_odra_template(&self, template_name: &str) -> Result<String, Error> {
+        // Templates from the project's templates directory win over the upstream ones.
+        let project_template = paths::project_template_path(template_name, &self.project_root);
+        if project_template.exists() {
+            log::info(format!("Using {}", project_template.display()));
+            return read_file_content(project_template)
+                .map_err(|err| Error::FailedToFetchTemplate(template_name.to_owned(), err.into()));
+        }
+
         match self.odra_location.clone() {
             OdraLocation::Local(path) => {
                 let path = path
                     .join("templates")
-                    .join(template_name)
-                    .with_extension("rs.template");
-                read_file_content(path)
-                    .map_err(|_| Error::FailedToFetchTemplate(template_name.to_owned()))
-            }
-            OdraLocation::Remote(_, branch) => {
-                let branch = branch.unwrap_or_else(|| "releases/latest".to_string());
-                let template_path = self.template_path(template_name, branch);
-                get(&template_path)
-                    .call()
-                    .map_err(|_| Error::FailedToFetchTemplate(template_path.clone()))
-                    .and_then(|res| {
-                        res.into_string()
-                            .map_err(|_| Error::FailedToParseTemplate(template_path.clone()))
-                    })
+                    .join(paths::template_file_name(template_name));
+                read_file_content(path).map_err(|err| {
+                    Error::FailedToFetchTemplate(template_name.to_owned(), err.into())
+                })
             }
+            OdraLocation::Remote(_, reference) => match reference {
+                Some(reference) => self.fetch_remote_template(
+                    template_name,
+                    reference.name(),
+                    reference.is_immutable(),
+                ),
+                None => self.fetch_remote_template(template_name, "releases/latest", false),
+            },
             OdraLocation::CratesIO(version) => {
+                let cached_source = paths::odra_source_cache_path(&version)
+                    .join("templates")
+                    .join(paths::template_file_name(template_name));
+                if let Ok(template) = read_file_content(cached_source) {
+                    return Ok(template);
+                }
                 let branch = format!("release/{}", version);
-                let template_path = self.template_path(template_name, branch);
-                get(&template_path)
-                    .call()
-                    .map_err(|_| Error::FailedToFetchTemplate(template_path.clone()))
-                    .and_then(|res| {
-                        res.into_string()
-                            .map_err(|_| Error::FailedToParseTemplate(template_path.clone()))
-                    })
+                self.fetch_remote_template(template_name, &branch, true)
+            }
+        }
+    }
+
+    /// Fetches a template from the repository, using the local cache when possible.
+    ///
+    /// Release branches do not change, so their cached templates are always used. Other
+    /// branches are downloaded again, and the cache is used only if that fails.
+    fn fetch_remote_template(
+        &self,
+        template_name: &str,
+        branch: &str,
+        immutable: bool,
+    ) -> Result<String, Error> {
+        let cache_path = paths::template_cache_path(branch, template_name);
+        let cached = read_file_content(cache_path.clone()).ok();
+        if let Some(cached) = cached.clone() {
+            if immutable || self.offline {
+                return Ok(cached);
+            }
+        }
+
+        if self.offline {
+            return Err(Error::TemplateNotCached(
+                template_name.to_owned(),
+                branch.to_owned(),
+            ));
+        }
+
+        match self.download_template(template_name, branch) {
+            Ok(template) => {
+                command::mkdir(cache_path.parent().unwrap().to_path_buf());
+                command::write_to_file(cache_path, &template);
+                Ok(template)
             }
+            Err(err) => match cached {
+                Some(cached) => {
+                    let causes = err.causes().join(" ");
+                    log::warn(format!("{err} {causes} Using cached template instead."));
+                    Ok(cached)
+                }
+                None => Err(err),
+            },
         }
     }
 
+    fn download_template(&self, template_name: &str, branch: &str) -> Result<String, Error> {
+        let template_path = self.template_path(template_name, branch);
+        http::get(&template_path)
+            .map_err(|err| Error::FailedToFetchTemplate(template_name.to_owned(), err.into()))?
+            .into_string()
+            .map_err(|_| Error::FailedToParseTemplate(template_path.clone()))
+    }
+
     /// Returns content of the new module file.
     pub fn module_template(&self, module_name: &str) -> Result<String, Error> {
         Ok(self
@@ -70,6 +160,35 @@ impl TemplateGenerator {
             .replace("#module_name", module_name))
     }
 
+    /// Returns content of the new non-deployable module file.
+    pub fn submodule_template(&self, module_name: &str) -> Result<String, Error> {
+        Ok(self
+            .fetch_template(SUBMODULE_TEMPLATE)?
+            .replace("#module_name", module_name))
+    }
+
+    /// Returns code of a new event.
+    pub fn event_template(&self, event_name: &str) -> Result<String, Error> {
+        Ok(self
+            .fetch_template(EVENT_TEMPLATE)?
+            .replace("#event_name", event_name))
+    }
+
+    /// Returns content of the new error enum file.
+    pub fn error_template(&self, error_name: &str) -> Result<String, Error> {
+        Ok(self
+            .fetch_template(ERROR_TEMPLATE)?
+            .replace("#error_name", error_name))
+    }
+
+    /// Returns code of a test module for an existing contract.
+    pub fn test_template(&self, contract_name: &str, module_name: &str) -> Result<String, Error> {
+        Ok(self
+            .fetch_template(TEST_TEMPLATE)?
+            .replace("#contract_name", contract_name)
+            .replace("#module_name", module_name))
+    }
+
     /// Returns code for src/lib.rs that registers a new module.
     pub fn register_module_snippet(
         &self,
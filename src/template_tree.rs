@@ -0,0 +1,253 @@
+//! Module providing project templates from a local copy of the Odra repository.
+
+use std::{
+    env,
+    path::{Path, PathBuf},
+    process,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use serde_derive::Deserialize;
+
+use crate::{
+    command,
+    consts::{
+        ODRA_PROJECT_TEMPLATES_DIR,
+        ODRA_TEMPLATE_CRATE_NAME_PLACEHOLDERS,
+        ODRA_TEMPLATE_DEFAULT_TEMPLATE,
+        PROJECT_TEMPLATE_DESCRIPTIONS,
+        PROJECT_TEMPLATE_METADATA_FILE,
+    },
+    errors::Error,
+    fs,
+    http,
+    log,
+    paths,
+    project::{GitReference, OdraLocation},
+};
+
+/// Number of temporary trees created by the process, keeping their directories apart.
+static TEMPORARY_TREES: AtomicUsize = AtomicUsize::new(0);
+
+/// Project template available in a template tree.
+#[derive(Debug, Clone)]
+pub struct ProjectTemplate {
+    pub name: String,
+    pub description: String,
+}
+
+/// Content of a template's metadata file.
+#[derive(Deserialize, Debug, Default)]
+struct TemplateMetadata {
+    description: Option<String>,
+}
+
+/// Local directory with Odra's project templates.
+pub struct TemplateTree {
+    root: PathBuf,
+    temporary: bool,
+}
+
+impl TemplateTree {
+    /// Returns the template tree of the given Odra location. Released versions are cached,
+    /// other remote locations are cloned into a temporary directory, removed when the tree
+    /// is dropped. If a repository is given, the templates are taken from it: from its
+    /// default branch for local locations and from the release branch for versions.
+    pub fn fetch(
+        odra_location: &OdraLocation,
+        repository: Option<&str>,
+        offline: bool,
+    ) -> Result<TemplateTree, Error> {
+        match (odra_location, repository) {
+            (OdraLocation::Local(path), None) => Ok(TemplateTree {
+                root: path.clone(),
+                temporary: false,
+            }),
+            (OdraLocation::Local(_), Some(repository)) => {
+                Self::fetch_temporary(repository, None, offline)
+            }
+            (OdraLocation::CratesIO(version), None) => Self::fetch_version(version, offline),
+            (OdraLocation::CratesIO(version), Some(repository)) => {
+                if repository == http::template_repo() {
+                    return Self::fetch_version(version, offline);
+                }
+                let reference = GitReference::Branch(format!("release/{version}"));
+                Self::fetch_temporary(repository, Some(&reference), offline)
+            }
+            (OdraLocation::Remote(repository, reference), _) => {
+                Self::fetch_temporary(repository, reference.as_ref(), offline)
+            }
+        }
+    }
+
+    /// Returns the cached tree of a released version, fetching it first if needed.
+    pub fn fetch_version(version: &str, offline: bool) -> Result<TemplateTree, Error> {
+        let root = paths::odra_source_cache_path(version);
+        if root.exists() {
+            log::verbose(format!("Using cached Odra {version}: {}", root.display()));
+            return Ok(TemplateTree {
+                root,
+                temporary: false,
+            });
+        }
+        if offline {
+            return Err(Error::OdraNotCached(version.to_string()));
+        }
+
+        // The tree is cloned next to the cache and moved into it once complete, so an
+        // interrupted clone is never taken for a cached version.
+        let partial = root.with_file_name(format!(".{version}.partial-{}", process::id()));
+        let branch = GitReference::Branch(format!("release/{version}"));
+        let cloned = command::git_clone(&http::template_repo(), Some(&branch), &partial)
+            // The history is not needed, release branches do not change.
+            .and_then(|_| fs::remove_dir(&partial.join(".git")))
+            .and_then(|_| {
+                // The cache is needed even in dry-run mode, so it is not moved through `fs`.
+                std::fs::rename(&partial, &root).or_else(|err| match root.exists() {
+                    // Fetched by another process in the meantime.
+                    true => Ok(()),
+                    false => Err(Error::FailedToRenameFile(
+                        partial.clone(),
+                        root.clone(),
+                        err,
+                    )),
+                })
+            });
+        let _ = std::fs::remove_dir_all(&partial);
+        cloned?;
+        Ok(TemplateTree {
+            root,
+            temporary: false,
+        })
+    }
+
+    /// Clones the repository into a temporary directory.
+    fn fetch_temporary(
+        repository: &str,
+        reference: Option<&GitReference>,
+        offline: bool,
+    ) -> Result<TemplateTree, Error> {
+        if offline {
+            return Err(Error::SourceNotAvailableOffline(match reference {
+                Some(reference) => format!("{repository} at {reference}"),
+                None => repository.to_string(),
+            }));
+        }
+        let tree = TemplateTree {
+            root: env::temp_dir().join(format!(
+                "cargo-odra-{}-{}",
+                process::id(),
+                TEMPORARY_TREES.fetch_add(1, Ordering::Relaxed)
+            )),
+            temporary: true,
+        };
+        command::git_clone(repository, reference, &tree.root)?;
+        Ok(tree)
+    }
+
+    /// Root directory of the tree.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Lists templates of the tree, sorted by name.
+    pub fn templates(&self) -> Vec<ProjectTemplate> {
+        let mut templates: Vec<ProjectTemplate> = self
+            .templates_dir()
+            .read_dir()
+            .map(|entries| entries.filter_map(Result::ok).collect())
+            .unwrap_or_else(|_| vec![])
+            .iter()
+            .filter(|entry| entry.path().is_dir())
+            .map(|entry| {
+                let name = entry.file_name().to_string_lossy().to_string();
+                ProjectTemplate {
+                    description: Self::description(&name, &entry.path()),
+                    name,
+                }
+            })
+            .collect();
+        templates.sort_by(|a, b| a.name.cmp(&b.name));
+        templates
+    }
+
+    /// Checks if the template exists, and if not, fails with the names of similar ones.
+    pub fn assert_template_exists(&self, name: &str) -> Result<(), Error> {
+        let names: Vec<String> = self
+            .templates()
+            .into_iter()
+            .map(|template| template.name)
+            .collect();
+        if names.iter().any(|template| template == name) {
+            return Ok(());
+        }
+
+        let similar: Vec<&String> = names
+            .iter()
+            .filter(|template| {
+                strsim::jaro_winkler(name, template) > 0.8
+                    || template.starts_with(name)
+                    || name.starts_with(template.as_str())
+            })
+            .collect();
+        let hint = match (similar.is_empty(), names.is_empty()) {
+            (_, true) => format!("There are no templates in {}.", self.root.display()),
+            (false, false) => format!("Did you mean {}?", join(&similar)),
+            (true, false) => format!(
+                "Available templates: {}.",
+                join(&names.iter().collect::<Vec<_>>())
+            ),
+        };
+        Err(Error::ProjectTemplateNotFound(name.to_string(), hint))
+    }
+
+    /// Reads a file of the default project template, with the crate name filled in.
+    pub fn project_file(&self, file: &str, crate_name: &str) -> Result<String, Error> {
+        let path = self
+            .templates_dir()
+            .join(ODRA_TEMPLATE_DEFAULT_TEMPLATE)
+            .join(file);
+        let content = command::read_file_content(path.clone())
+            .map_err(|err| Error::FailedToReadFile(path, err))?;
+        Ok(ODRA_TEMPLATE_CRATE_NAME_PLACEHOLDERS
+            .iter()
+            .fold(content, |content, placeholder| {
+                content.replace(placeholder, crate_name)
+            }))
+    }
+
+    fn templates_dir(&self) -> PathBuf {
+        self.root.join(ODRA_PROJECT_TEMPLATES_DIR)
+    }
+
+    /// Reads the template description from its metadata file, falling back to the known ones.
+    fn description(name: &str, template_dir: &Path) -> String {
+        command::read_file_content(template_dir.join(PROJECT_TEMPLATE_METADATA_FILE))
+            .ok()
+            .and_then(|content| toml::from_str::<TemplateMetadata>(&content).ok())
+            .and_then(|metadata| metadata.description)
+            .or_else(|| {
+                PROJECT_TEMPLATE_DESCRIPTIONS
+                    .iter()
+                    .find(|(template, _)| *template == name)
+                    .map(|(_, description)| description.to_string())
+            })
+            .unwrap_or_default()
+    }
+}
+
+impl Drop for TemplateTree {
+    fn drop(&mut self) {
+        if self.temporary {
+            let _ = std::fs::remove_dir_all(&self.root);
+        }
+    }
+}
+
+fn join(names: &[&String]) -> String {
+    names
+        .iter()
+        .map(|name| format!("\"{name}\""))
+        .collect::<Vec<_>>()
+        .join(", ")
+}